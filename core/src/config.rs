@@ -1,9 +1,20 @@
-use crate::{ListNode, TabList};
+use crate::{
+    Command, Error, ListNode, LookupError, TabList, dirs::config_dir, inner::read_toml,
+    is_known_task_flag, validate::is_variable_name,
+};
 use serde::Deserialize;
-use std::{fs, path::Path, process, rc::Rc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// System-wide defaults, overridden by the user's configuration
+pub const SYSTEM_CONFIG: &str = "/etc/osutil/config.toml";
 
 // Struct that defines what values can be used in the toml file
-#[derive(Deserialize)]
+#[derive(Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
@@ -12,48 +23,621 @@ pub struct Config {
     skip_confirmation: Option<bool>,
     #[serde(default)]
     size_bypass: Option<bool>,
+    #[serde(default)]
+    tabs_dirs: Option<Vec<PathBuf>>,
+    #[serde(default)]
+    on_failure: Option<FailurePolicy>,
+    #[serde(default)]
+    exclude_flags: Option<Vec<String>>,
+    #[serde(default)]
+    entries: EntrySettings,
+    /// The files merged into this configuration, lowest precedence first
+    #[serde(skip)]
+    files: Vec<PathBuf>,
+    /// Where each value was set, by key. Tab directories are keyed `tabs_dirs.<path>`, excluded
+    /// flags `exclude_flags.<flag>` and entry variables `entries.<entry>.<variable>`.
+    #[serde(skip)]
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+/// Where a configuration value was set
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    File(PathBuf),
+    CommandLine,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::File(path) => write!(f, "{}", path.display()),
+            ConfigSource::CommandLine => f.write_str("command line"),
+        }
+    }
+}
+
+/// Settings of one entry, from an `[entries."<name>"]` table
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EntryConfig {
+    /// Environment variables for the entry's script. They also answer its inputs.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// The `[entries."<name>"]` tables, by entry name
+#[derive(Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct EntrySettings(HashMap<String, EntryConfig>);
+
+/// An `[entries."<name>"]` table that has no effect
+#[derive(Clone, Debug)]
+pub enum UnusedEntrySettings {
+    /// No entry has this name
+    NotFound(String),
+    /// Every entry with this name is a command or a directory, which take no variables
+    NotScript(String),
+}
+
+impl fmt::Display for UnusedEntrySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnusedEntrySettings::NotFound(name) => {
+                write!(
+                    f,
+                    "[entries.{name:?}] has no effect, there is no such entry"
+                )
+            }
+            UnusedEntrySettings::NotScript(name) => {
+                write!(
+                    f,
+                    "[entries.{name:?}] has no effect, {name} is not a script entry"
+                )
+            }
+        }
+    }
+}
+
+/// What to do with the rest of a batch when one of its entries fails
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Skip the remaining entries
+    #[default]
+    Stop,
+    /// Run the remaining entries anyway, and report every failure at the end
+    Continue,
+    /// Ask whether to run the remaining entries
+    Prompt,
 }
 
 // Struct that holds the parsed values from the toml so that it can be applied in the AppState
 pub struct ConfigValues {
     pub auto_execute_commands: Vec<Rc<ListNode>>,
+    /// Names in `auto_execute` that do not resolve to an entry
+    pub auto_execute_errors: Vec<LookupError>,
+    /// `[entries."<name>"]` tables that do not apply to any entry
+    pub unused_entries: Vec<UnusedEntrySettings>,
     pub skip_confirmation: bool,
     pub size_bypass: bool,
+    pub on_failure: FailurePolicy,
+    pub entries: EntrySettings,
+}
+
+impl EntrySettings {
+    /// The entries with their settings applied, for those that have some
+    pub fn apply(&self, nodes: &[Rc<ListNode>]) -> Vec<Rc<ListNode>> {
+        nodes
+            .iter()
+            .map(|node| match self.0.get(&node.name) {
+                Some(config) if !config.env.is_empty() => Rc::new(ListNode {
+                    command: node.command.with_env(config.env.clone()),
+                    ..ListNode::clone(node)
+                }),
+                _ => node.clone(),
+            })
+            .collect()
+    }
+}
+
+impl FailurePolicy {
+    /// The next policy, to cycle through them
+    pub fn next(self) -> Self {
+        match self {
+            FailurePolicy::Stop => FailurePolicy::Continue,
+            FailurePolicy::Continue => FailurePolicy::Prompt,
+            FailurePolicy::Prompt => FailurePolicy::Stop,
+        }
+    }
+}
+
+impl FailurePolicy {
+    /// The name used in the config file
+    fn key(self) -> &'static str {
+        match self {
+            FailurePolicy::Stop => "stop",
+            FailurePolicy::Continue => "continue",
+            FailurePolicy::Prompt => "prompt",
+        }
+    }
+}
+
+impl fmt::Display for FailurePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FailurePolicy::Stop => "stop at the first failure",
+            FailurePolicy::Continue => "continue and report",
+            FailurePolicy::Prompt => "ask what to do",
+        })
+    }
 }
 
 impl Config {
-    pub fn read_config(path: &Path, tabs: &TabList) -> ConfigValues {
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!("Failed to read config file {}: {}", path.display(), e);
-                process::exit(1);
+    pub fn read_config(path: &Path) -> Result<Config, Error> {
+        let mut config: Config = read_toml(path)?;
+
+        if let Some(flag) = config
+            .exclude_flags()
+            .iter()
+            .find(|flag| !is_known_task_flag(&flag.to_ascii_uppercase()))
+        {
+            return Err(Error::Invalid {
+                path: path.to_path_buf(),
+                message: format!("{flag:?} in exclude_flags is not a known task flag"),
+            });
+        }
+
+        let mut names: Vec<_> = config.entries.0.iter().collect();
+        names.sort_by_key(|(name, _)| *name);
+        for (name, entry) in names {
+            if let Some(key) = entry.env.keys().find(|key| !is_variable_name(key)) {
+                return Err(Error::Invalid {
+                    path: path.to_path_buf(),
+                    message: format!(
+                        "{key:?} in the env of {name:?} is not a valid environment variable name"
+                    ),
+                });
             }
-        };
+        }
+
+        // Relative tab directories are relative to the config file
+        if let (Some(tabs_dirs), Some(config_dir)) = (&mut config.tabs_dirs, path.parent()) {
+            for dir in tabs_dirs.iter_mut() {
+                *dir = config_dir.join(&*dir);
+            }
+        }
+        Ok(config)
+    }
+
+    /// The configuration files read by default, lowest precedence first: [`SYSTEM_CONFIG`], then
+    /// `$XDG_CONFIG_HOME/osutil/config.toml`. Files that do not exist are left out.
+    pub fn default_files() -> Vec<PathBuf> {
+        let user_config = config_dir().map(|dir| dir.join("config.toml"));
+        std::iter::once(PathBuf::from(SYSTEM_CONFIG))
+            .chain(user_config)
+            .filter(|path| path.is_file())
+            .collect()
+    }
+
+    /// Read the files and merge them in order, each overriding the values of the ones before it.
+    /// Tab directories and excluded flags add up, and entry variables are merged one by one. Files that cannot be
+    /// read are skipped and their errors returned.
+    pub fn read_layered(files: &[PathBuf]) -> (Config, Vec<Error>) {
+        let mut config = Config::default();
+        let mut errors = Vec::new();
+        for file in files {
+            match Config::read_config(file) {
+                Ok(layer) => config.merge(layer, &ConfigSource::File(file.clone())),
+                Err(e) => errors.push(e),
+            }
+        }
+        (config, errors)
+    }
+
+    fn merge(&mut self, layer: Config, source: &ConfigSource) {
+        let mut set = |key: String| self.sources.insert(key, source.clone());
+        if layer.auto_execute.is_some() {
+            set("auto_execute".to_string());
+        }
+        if layer.skip_confirmation.is_some() {
+            set("skip_confirmation".to_string());
+        }
+        if layer.size_bypass.is_some() {
+            set("size_bypass".to_string());
+        }
+        if layer.on_failure.is_some() {
+            set("on_failure".to_string());
+        }
+        for dir in layer.tabs_dirs() {
+            set(format!("tabs_dirs.{}", dir.display()));
+        }
+        for flag in layer.exclude_flags() {
+            set(format!("exclude_flags.{flag}"));
+        }
+        for (name, entry) in &layer.entries.0 {
+            for key in entry.env.keys() {
+                set(format!("entries.{name}.{key}"));
+            }
+        }
 
-        let config: Config = match toml::from_str(&content) {
-            Ok(config) => config,
-            Err(e) => {
-                eprintln!("Failed to parse config file: {e}");
-                process::exit(1);
+        self.auto_execute = layer.auto_execute.or(self.auto_execute.take());
+        self.skip_confirmation = layer.skip_confirmation.or(self.skip_confirmation);
+        self.size_bypass = layer.size_bypass.or(self.size_bypass);
+        self.on_failure = layer.on_failure.or(self.on_failure);
+        if let Some(tabs_dirs) = layer.tabs_dirs {
+            self.tabs_dirs.get_or_insert_default().extend(tabs_dirs);
+        }
+        if let Some(flags) = layer.exclude_flags {
+            let merged = self.exclude_flags.get_or_insert_default();
+            for flag in flags {
+                if !merged.contains(&flag) {
+                    merged.push(flag);
+                }
             }
+        }
+        for (name, entry) in layer.entries.0 {
+            let merged = self.entries.0.entry(name).or_default();
+            merged.env.extend(entry.env);
+        }
+        if let ConfigSource::File(file) = source {
+            self.files.push(file.clone());
+        }
+    }
+
+    /// Apply the flags given on the command line, which take precedence over every file. Flags
+    /// that are not given leave the configuration as it is.
+    pub fn apply_command_line(
+        &mut self,
+        skip_confirmation: bool,
+        size_bypass: bool,
+        tabs_dirs: &[PathBuf],
+        exclude_flags: &[String],
+    ) {
+        let layer = Config {
+            skip_confirmation: skip_confirmation.then_some(true),
+            size_bypass: size_bypass.then_some(true),
+            tabs_dirs: (!tabs_dirs.is_empty()).then(|| tabs_dirs.to_vec()),
+            exclude_flags: (!exclude_flags.is_empty()).then(|| exclude_flags.to_vec()),
+            ..Config::default()
         };
+        self.merge(layer, &ConfigSource::CommandLine);
+    }
 
-        ConfigValues {
-            auto_execute_commands: config.auto_execute_commands(tabs),
-            skip_confirmation: config.skip_confirmation.unwrap_or(false),
-            size_bypass: config.size_bypass.unwrap_or(false),
+    /// The effective configuration in the config file format, with a comment after each value
+    /// naming where it was set. Entry variables answering a password input of an entry in
+    /// `tabs` are hidden, and so are all entry variables when the tabs could not be loaded.
+    pub fn show(&self, tabs: Option<&TabList>) -> String {
+        let source = |key: &str| match self.sources.get(key) {
+            Some(source) => source.to_string(),
+            None => "default".to_string(),
+        };
+        let string = |value: &str| toml::Value::String(value.to_string()).to_string();
+        let is_password = |name: &str, key: &str| {
+            tabs.is_none_or(|tabs| {
+                tabs.iter()
+                    .flat_map(|tab| tab.tree.root().descendants())
+                    .any(|node| {
+                        node.value().name == name
+                            && node.value().password_inputs().any(|input| input == key)
+                    })
+            })
+        };
+        let mut out = String::new();
+        if self.files.is_empty() {
+            out.push_str("# No configuration files were read\n");
+        } else {
+            out.push_str("# Files read, lowest precedence first:\n");
+            for file in &self.files {
+                out.push_str(&format!("#   {}\n", file.display()));
+            }
         }
+        out.push('\n');
+
+        let auto_execute: Vec<String> = self
+            .auto_execute
+            .iter()
+            .flatten()
+            .map(|name| string(name))
+            .collect();
+        out.push_str(&format!(
+            "auto_execute = [{}] # {}\n",
+            auto_execute.join(", "),
+            source("auto_execute")
+        ));
+        out.push_str(&format!(
+            "skip_confirmation = {} # {}\n",
+            self.skip_confirmation.unwrap_or(false),
+            source("skip_confirmation")
+        ));
+        out.push_str(&format!(
+            "size_bypass = {} # {}\n",
+            self.size_bypass.unwrap_or(false),
+            source("size_bypass")
+        ));
+        out.push_str(&format!(
+            "on_failure = {} # {}\n",
+            string(self.on_failure().key()),
+            source("on_failure")
+        ));
+        if self.tabs_dirs().is_empty() {
+            out.push_str("tabs_dirs = [] # default\n");
+        } else {
+            out.push_str("tabs_dirs = [\n");
+            for dir in self.tabs_dirs() {
+                let dir = dir.display().to_string();
+                out.push_str(&format!(
+                    "    {}, # {}\n",
+                    string(&dir),
+                    source(&format!("tabs_dirs.{dir}"))
+                ));
+            }
+            out.push_str("]\n");
+        }
+        if self.exclude_flags().is_empty() {
+            out.push_str("exclude_flags = [] # default\n");
+        } else {
+            out.push_str("exclude_flags = [\n");
+            for flag in self.exclude_flags() {
+                out.push_str(&format!(
+                    "    {}, # {}\n",
+                    string(flag),
+                    source(&format!("exclude_flags.{flag}"))
+                ));
+            }
+            out.push_str("]\n");
+        }
+
+        let mut entries: Vec<_> = self.entries.0.iter().collect();
+        entries.sort_by_key(|(name, _)| *name);
+        for (name, entry) in entries {
+            out.push_str(&format!("\n[entries.{}.env]\n", string(name)));
+            for (key, value) in &entry.env {
+                let key_source = source(&format!("entries.{name}.{key}"));
+                let value = if is_password(name, key) {
+                    string("<hidden>")
+                } else {
+                    string(value)
+                };
+                out.push_str(&format!("{key} = {value} # {key_source}\n"));
+            }
+        }
+        out
+    }
+
+    /// Extra tab directories to load in addition to the built-in tabs
+    pub fn tabs_dirs(&self) -> &[PathBuf] {
+        self.tabs_dirs.as_deref().unwrap_or_default()
+    }
+
+    /// Task flags whose entries are hidden
+    pub fn exclude_flags(&self) -> &[String] {
+        self.exclude_flags.as_deref().unwrap_or_default()
+    }
+
+    /// What to do when an entry of a batch fails
+    pub fn on_failure(&self) -> FailurePolicy {
+        self.on_failure.unwrap_or_default()
+    }
+
+    /// Settings applied to entries whenever they run
+    pub fn entries(&self) -> &EntrySettings {
+        &self.entries
     }
 
-    fn auto_execute_commands(&self, tabs: &TabList) -> Vec<Rc<ListNode>> {
-        self.auto_execute
-            .as_ref()
-            .map_or_else(Vec::new, |commands| {
-                commands
+    /// The `[entries."<name>"]` tables naming no entry of `tabs`, or only entries that are not
+    /// scripts. Entries hidden on this system count when the tabs were loaded with them kept.
+    pub fn unused_entries(&self, tabs: &TabList) -> Vec<UnusedEntrySettings> {
+        let mut names: Vec<&String> = self.entries.0.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let mut entries = tabs
                     .iter()
-                    .filter_map(|name| tabs.iter().find_map(|tab| tab.find_command_by_name(name)))
-                    .collect()
+                    .flat_map(|tab| tab.tree.root().descendants())
+                    .filter(|node| node.value().name == *name)
+                    .peekable();
+                if entries.peek().is_none() {
+                    Some(UnusedEntrySettings::NotFound(name.clone()))
+                } else if !entries
+                    .any(|node| matches!(node.value().command, Command::LocalFile { .. }))
+                {
+                    Some(UnusedEntrySettings::NotScript(name.clone()))
+                } else {
+                    None
+                }
             })
+            .collect()
+    }
+
+    pub fn values(&self, tabs: &TabList) -> ConfigValues {
+        let auto_execute = self.auto_execute.as_deref().unwrap_or_default();
+        let (auto_execute_commands, auto_execute_errors) = tabs.resolve_entries(auto_execute);
+        ConfigValues {
+            auto_execute_commands,
+            auto_execute_errors,
+            unused_entries: self.unused_entries(tabs),
+            skip_confirmation: self.skip_confirmation.unwrap_or(false),
+            size_bypass: self.size_bypass.unwrap_or(false),
+            on_failure: self.on_failure(),
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigSource, FailurePolicy};
+    use crate::Error;
+    use std::path::{Path, PathBuf};
+    use temp_dir::TempDir;
+
+    const SYSTEM: &str = "/etc/osutil/config.toml";
+    const USER: &str = "/home/user/.config/osutil/config.toml";
+    const EXTRA: &str = "/tmp/extra.toml";
+
+    /// Merge configuration files given as `(path, content)`, lowest precedence first
+    fn layered(files: &[(&str, &str)]) -> Config {
+        let mut config = Config::default();
+        for (path, content) in files {
+            let layer: Config = toml::from_str(content).unwrap();
+            config.merge(layer, &ConfigSource::File(PathBuf::from(path)));
+        }
+        config
+    }
+
+    fn source(config: &Config, key: &str) -> Option<String> {
+        config.sources.get(key).map(ToString::to_string)
+    }
+
+    fn env(config: &Config, entry: &str, key: &str) -> Option<String> {
+        config.entries.0.get(entry)?.env.get(key).cloned()
+    }
+
+    #[test]
+    fn later_files_override_earlier_ones() {
+        let config = layered(&[
+            (SYSTEM, "on_failure = \"stop\"\nskip_confirmation = true"),
+            (USER, "on_failure = \"continue\""),
+            (EXTRA, "on_failure = \"prompt\"\nauto_execute = [\"A\"]"),
+        ]);
+        assert_eq!(config.on_failure(), FailurePolicy::Prompt);
+        assert_eq!(source(&config, "on_failure").as_deref(), Some(EXTRA));
+        assert_eq!(config.skip_confirmation, Some(true));
+        assert_eq!(
+            source(&config, "skip_confirmation").as_deref(),
+            Some(SYSTEM)
+        );
+        assert_eq!(config.auto_execute, Some(vec!["A".to_string()]));
+        assert_eq!(config.files, [SYSTEM, USER, EXTRA].map(PathBuf::from));
+    }
+
+    #[test]
+    fn unset_keys_keep_earlier_values() {
+        let config = layered(&[
+            (SYSTEM, "auto_execute = [\"A\", \"B\"]\nsize_bypass = true"),
+            (USER, ""),
+        ]);
+        assert_eq!(
+            config.auto_execute,
+            Some(vec!["A".to_string(), "B".to_string()])
+        );
+        assert_eq!(config.size_bypass, Some(true));
+        assert_eq!(source(&config, "size_bypass").as_deref(), Some(SYSTEM));
+        assert_eq!(source(&config, "on_failure"), None);
+        assert_eq!(config.on_failure(), FailurePolicy::Stop);
+    }
+
+    #[test]
+    fn lists_add_up_across_files() {
+        let config = layered(&[
+            (
+                SYSTEM,
+                "tabs_dirs = [\"/a\"]\nexclude_flags = [\"K\", \"P\"]",
+            ),
+            (USER, "tabs_dirs = [\"/b\"]\nexclude_flags = [\"P\", \"S\"]"),
+        ]);
+        assert_eq!(config.tabs_dirs(), [Path::new("/a"), Path::new("/b")]);
+        assert_eq!(config.exclude_flags(), ["K", "P", "S"]);
+        assert_eq!(source(&config, "tabs_dirs./a").as_deref(), Some(SYSTEM));
+        assert_eq!(source(&config, "tabs_dirs./b").as_deref(), Some(USER));
+        assert_eq!(source(&config, "exclude_flags.K").as_deref(), Some(SYSTEM));
+        assert_eq!(source(&config, "exclude_flags.P").as_deref(), Some(USER));
+    }
+
+    #[test]
+    fn entry_variables_merge_one_by_one() {
+        let config = layered(&[
+            (
+                SYSTEM,
+                "[entries.\"Add User\"]\nenv = { NAME = \"alice\", SHELL = \"bash\" }",
+            ),
+            (USER, "[entries.\"Add User\"]\nenv = { NAME = \"bob\" }"),
+        ]);
+        assert_eq!(env(&config, "Add User", "NAME").as_deref(), Some("bob"));
+        assert_eq!(env(&config, "Add User", "SHELL").as_deref(), Some("bash"));
+        assert_eq!(
+            source(&config, "entries.Add User.NAME").as_deref(),
+            Some(USER)
+        );
+        assert_eq!(
+            source(&config, "entries.Add User.SHELL").as_deref(),
+            Some(SYSTEM)
+        );
+    }
+
+    #[test]
+    fn command_line_overrides_every_file() {
+        let mut config = layered(&[
+            (SYSTEM, "skip_confirmation = false\ntabs_dirs = [\"/a\"]"),
+            (EXTRA, "size_bypass = false\nexclude_flags = [\"K\"]"),
+        ]);
+        config.apply_command_line(true, true, &[PathBuf::from("/b")], &["S".to_string()]);
+        assert_eq!(config.skip_confirmation, Some(true));
+        assert_eq!(config.size_bypass, Some(true));
+        assert_eq!(config.tabs_dirs(), [Path::new("/a"), Path::new("/b")]);
+        assert_eq!(config.exclude_flags(), ["K", "S"]);
+        let command_line = Some(ConfigSource::CommandLine.to_string());
+        assert_eq!(source(&config, "skip_confirmation"), command_line);
+        assert_eq!(source(&config, "size_bypass"), command_line);
+        assert_eq!(source(&config, "tabs_dirs./b"), command_line);
+        assert_eq!(source(&config, "tabs_dirs./a").as_deref(), Some(SYSTEM));
+        // The command line is not a file
+        assert_eq!(config.files, [SYSTEM, EXTRA].map(PathBuf::from));
+    }
+
+    #[test]
+    fn flags_not_given_leave_the_files_alone() {
+        let mut config = layered(&[(USER, "skip_confirmation = true\nsize_bypass = false")]);
+        config.apply_command_line(false, false, &[], &[]);
+        assert_eq!(config.skip_confirmation, Some(true));
+        assert_eq!(config.size_bypass, Some(false));
+        assert_eq!(source(&config, "skip_confirmation").as_deref(), Some(USER));
+        assert_eq!(source(&config, "size_bypass").as_deref(), Some(USER));
+    }
+
+    #[test]
+    fn show_names_the_source_of_each_value() {
+        let mut config = layered(&[
+            (SYSTEM, "on_failure = \"continue\""),
+            (USER, "[entries.E]\nenv = { NAME = \"alice\" }"),
+        ]);
+        config.apply_command_line(true, false, &[], &[]);
+        let shown = config.show(None);
+        assert!(shown.contains(&format!("#   {SYSTEM}\n#   {USER}\n")));
+        assert!(shown.contains(&format!("on_failure = \"continue\" # {SYSTEM}\n")));
+        assert!(shown.contains("skip_confirmation = true # command line\n"));
+        assert!(shown.contains("size_bypass = false # default\n"));
+        // Without tabs every entry variable may be a password
+        assert!(shown.contains(&format!("NAME = \"<hidden>\" # {USER}\n")));
+    }
+
+    #[test]
+    fn read_layered_skips_invalid_files() {
+        let dir = TempDir::new().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let good = write("good.toml", "tabs_dirs = [\"tabs\"]");
+        let bad_flag = write("flag.toml", "exclude_flags = [\"Q\"]");
+        let bad_key = write("key.toml", "[entries.E]\nenv = { \"NOT-A-NAME\" = \"x\" }");
+        let unknown = write("unknown.toml", "no_such_key = true");
+        let (config, errors) = Config::read_layered(&[
+            good.clone(),
+            bad_flag,
+            bad_key,
+            unknown,
+            dir.path().join("missing.toml"),
+        ]);
+        // Relative tab directories are relative to the file
+        assert_eq!(config.tabs_dirs(), [dir.path().join("tabs")]);
+        assert_eq!(config.files, [good]);
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(errors[0], Error::Invalid { .. }));
+        assert!(matches!(errors[1], Error::Invalid { .. }));
+        assert!(matches!(errors[2], Error::Parse { .. }));
+        assert!(matches!(errors[3], Error::Read { .. }));
     }
 }
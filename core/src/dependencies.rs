@@ -0,0 +1,319 @@
+use crate::{Command, Error, ListNode, LookupError, Tab, TabList};
+use std::{collections::HashMap, fmt, rc::Rc};
+
+/// A `requires` entry that cannot run on this system, so the entry requiring it cannot either
+#[derive(Clone, Debug)]
+pub struct UnmetRequirement {
+    /// The entry with the requirement
+    pub entry: String,
+    /// Why the required entry cannot run, either [`LookupError::NotFound`] or
+    /// [`LookupError::Unavailable`]
+    pub error: LookupError,
+}
+
+impl fmt::Display for UnmetRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            LookupError::Unavailable { name, reason } => write!(
+                f,
+                "{} requires {name}, which is not available on this system: {reason}",
+                self.entry
+            ),
+            LookupError::NotFound(name) | LookupError::Ambiguous { name, .. } => {
+                write!(f, "{} requires {name}, which does not exist", self.entry)
+            }
+        }
+    }
+}
+
+impl TabList {
+    /// The selected entries together with every entry they require, transitively. Entries are
+    /// ordered after their prerequisites and after the entries they are listed `after`, and
+    /// otherwise keep the selection order. Prerequisites that are missing or hidden on this system
+    /// are left out and returned as unmet requirements instead; the tabs need to be loaded with
+    /// hidden entries kept to tell the two apart.
+    pub fn with_prerequisites(
+        &self,
+        selected: &[Rc<ListNode>],
+    ) -> (Vec<Rc<ListNode>>, Vec<UnmetRequirement>) {
+        let mut included: Vec<Rc<ListNode>> = Vec::new();
+        let mut unmet: Vec<UnmetRequirement> = Vec::new();
+        let mut queue: Vec<Rc<ListNode>> = selected.to_vec();
+        while !queue.is_empty() {
+            let node = queue.remove(0);
+            if included.iter().any(|other| other.name == node.name) {
+                continue;
+            }
+            for name in &node.requires {
+                match self.find_command_by_name(name) {
+                    Some(requirement) => queue.push(requirement),
+                    None => {
+                        if let Err(error) = self.resolve_entry(name) {
+                            unmet.push(UnmetRequirement {
+                                entry: node.name.clone(),
+                                error,
+                            });
+                        }
+                    }
+                }
+            }
+            included.push(node);
+        }
+
+        let mut ordered = Vec::with_capacity(included.len());
+        let mut visiting = Vec::new();
+        for node in &included {
+            visit(node, &included, &mut ordered, &mut visiting);
+        }
+        (ordered, unmet)
+    }
+}
+
+/// Depth-first visit adding the prerequisites of `node` before it. Cycles are reported when the
+/// tabs are loaded; here they are only cut short.
+fn visit<'a>(
+    node: &'a Rc<ListNode>,
+    included: &'a [Rc<ListNode>],
+    ordered: &mut Vec<Rc<ListNode>>,
+    visiting: &mut Vec<&'a str>,
+) {
+    if visiting.contains(&node.name.as_str()) || ordered.iter().any(|other| other.name == node.name)
+    {
+        return;
+    }
+    visiting.push(&node.name);
+    for dependency in node.requires.iter().chain(&node.after) {
+        if let Some(dependency) = included.iter().find(|other| other.name == *dependency) {
+            visit(dependency, included, ordered, visiting);
+        }
+    }
+    visiting.pop();
+    ordered.push(node.clone());
+}
+
+/// Find the cycles formed by `requires` and `after` between the entries of the tabs
+pub(crate) fn find_cycles(tabs: &[Tab]) -> Vec<Error> {
+    // Entries are referred to by name, so entries sharing a name share their dependencies
+    let mut names: Vec<&str> = Vec::new();
+    let mut graph: HashMap<&str, Vec<&str>> = HashMap::new();
+    for tab in tabs {
+        for node in tab.tree.root().descendants() {
+            let value = node.value();
+            if value.command == Command::None {
+                continue;
+            }
+            let edges = graph.entry(&value.name).or_insert_with(|| {
+                names.push(&value.name);
+                Vec::new()
+            });
+            edges.extend(
+                value
+                    .requires
+                    .iter()
+                    .chain(&value.after)
+                    .map(String::as_str),
+            );
+        }
+    }
+
+    let mut state = CycleSearch {
+        graph: &graph,
+        done: Vec::new(),
+        path: Vec::new(),
+        cycles: Vec::new(),
+    };
+    for name in names {
+        state.visit(name);
+    }
+    state
+        .cycles
+        .into_iter()
+        .map(|cycle| Error::DependencyCycle { cycle })
+        .collect()
+}
+
+struct CycleSearch<'a> {
+    graph: &'a HashMap<&'a str, Vec<&'a str>>,
+    done: Vec<&'a str>,
+    /// Entries currently being visited, in order
+    path: Vec<&'a str>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl<'a> CycleSearch<'a> {
+    fn visit(&mut self, name: &'a str) {
+        if self.done.contains(&name) {
+            return;
+        }
+        if let Some(start) = self.path.iter().position(|other| *other == name) {
+            let mut cycle: Vec<String> = self.path[start..].iter().map(|s| s.to_string()).collect();
+            cycle.push(name.to_string());
+            self.cycles.push(cycle);
+            return;
+        }
+        // Dependencies on entries that do not exist here are not part of any cycle
+        let Some(edges) = self.graph.get(name) else {
+            return;
+        };
+        self.path.push(name);
+        for dependency in edges {
+            self.visit(dependency);
+        }
+        self.path.pop();
+        self.done.push(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_cycles;
+    use crate::{Command, Error, HiddenReason, ListNode, LookupError, Tab, TabList};
+    use ego_tree::Tree;
+    use std::rc::Rc;
+
+    fn entry(name: &str, requires: &[&str], after: &[&str]) -> ListNode {
+        ListNode {
+            name: name.to_string(),
+            description: String::new(),
+            command: Command::Raw(format!("echo {name}")),
+            task_list: String::new(),
+            multi_select: true,
+            hidden: None,
+            requires: requires.iter().map(|name| name.to_string()).collect(),
+            after: after.iter().map(|name| name.to_string()).collect(),
+            inputs: Vec::new(),
+        }
+    }
+
+    fn tab(entries: Vec<ListNode>) -> Tab {
+        let mut tree = Tree::new(Rc::new(ListNode {
+            command: Command::None,
+            ..entry("root", &[], &[])
+        }));
+        for entry in entries {
+            tree.root_mut().append(Rc::new(entry));
+        }
+        Tab {
+            name: "Tab".to_string(),
+            tree,
+        }
+    }
+
+    fn tabs(entries: Vec<ListNode>) -> TabList {
+        TabList::from_tabs(vec![tab(entries)])
+    }
+
+    fn select(tabs: &TabList, names: &[&str]) -> Vec<Rc<ListNode>> {
+        names
+            .iter()
+            .map(|name| tabs.find_command_by_name(name).unwrap())
+            .collect()
+    }
+
+    /// Names of the selected entries with their prerequisites, in running order
+    fn run_order(tabs: &TabList, names: &[&str]) -> Vec<String> {
+        let (nodes, unmet) = tabs.with_prerequisites(&select(tabs, names));
+        assert!(unmet.is_empty());
+        nodes.iter().map(|node| node.name.clone()).collect()
+    }
+
+    fn cycles(entries: Vec<ListNode>) -> Vec<Vec<String>> {
+        find_cycles(&[tab(entries)])
+            .into_iter()
+            .map(|error| match error {
+                Error::DependencyCycle { cycle } => cycle,
+                error => panic!("unexpected error: {error}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selection_order_is_kept_without_dependencies() {
+        let tabs = tabs(vec![entry("A", &[], &[]), entry("B", &[], &[])]);
+        assert_eq!(run_order(&tabs, &["B", "A"]), ["B", "A"]);
+    }
+
+    #[test]
+    fn requirements_are_pulled_in_transitively() {
+        let tabs = tabs(vec![
+            entry("A", &["B"], &[]),
+            entry("B", &["C"], &[]),
+            entry("C", &[], &[]),
+        ]);
+        assert_eq!(run_order(&tabs, &["A"]), ["C", "B", "A"]);
+    }
+
+    #[test]
+    fn after_orders_without_pulling_in() {
+        let tabs = tabs(vec![entry("A", &[], &["B"]), entry("B", &[], &[])]);
+        assert_eq!(run_order(&tabs, &["A"]), ["A"]);
+        assert_eq!(run_order(&tabs, &["A", "B"]), ["B", "A"]);
+    }
+
+    #[test]
+    fn shared_requirements_run_once() {
+        let tabs = tabs(vec![
+            entry("A", &["B", "C"], &[]),
+            entry("B", &["D"], &[]),
+            entry("C", &["D"], &[]),
+            entry("D", &[], &[]),
+        ]);
+        assert_eq!(run_order(&tabs, &["A"]), ["D", "B", "C", "A"]);
+        assert_eq!(run_order(&tabs, &["C", "A"]), ["D", "C", "B", "A"]);
+    }
+
+    #[test]
+    fn cycles_are_cut_short() {
+        let tabs = tabs(vec![entry("A", &["B"], &[]), entry("B", &["A"], &[])]);
+        assert_eq!(run_order(&tabs, &["A"]), ["B", "A"]);
+    }
+
+    #[test]
+    fn missing_and_hidden_requirements_are_unmet() {
+        let hidden = ListNode {
+            hidden: Some(HiddenReason::NoSupportedEntries),
+            ..entry("Hidden", &[], &[])
+        };
+        let tabs = tabs(vec![entry("A", &["Missing", "Hidden"], &[]), hidden]);
+        let (nodes, unmet) = tabs.with_prerequisites(&select(&tabs, &["A"]));
+        let names: Vec<&str> = nodes.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, ["A"]);
+        assert_eq!(unmet.len(), 2);
+        assert!(unmet.iter().all(|requirement| requirement.entry == "A"));
+        assert!(matches!(&unmet[0].error, LookupError::NotFound(name) if name == "Missing"));
+        assert!(matches!(
+            &unmet[1].error,
+            LookupError::Unavailable { name, .. } if name == "Hidden"
+        ));
+        assert_eq!(
+            unmet[0].to_string(),
+            "A requires Missing, which does not exist"
+        );
+    }
+
+    #[test]
+    fn self_cycles_are_found() {
+        assert_eq!(cycles(vec![entry("A", &["A"], &[])]), [["A", "A"]]);
+    }
+
+    #[test]
+    fn cycles_through_requires_and_after_are_found() {
+        let found = cycles(vec![
+            entry("A", &["B"], &[]),
+            entry("B", &[], &["C"]),
+            entry("C", &["A"], &[]),
+        ]);
+        assert_eq!(found, [["A", "B", "C", "A"]]);
+    }
+
+    #[test]
+    fn diamonds_and_missing_entries_are_not_cycles() {
+        let found = cycles(vec![
+            entry("A", &["B", "C"], &[]),
+            entry("B", &["D"], &[]),
+            entry("C", &["D"], &["Missing"]),
+            entry("D", &[], &[]),
+        ]);
+        assert!(found.is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+use std::{
+    env,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>` when the variable is unset
+/// or not an absolute path, as the XDG base directory specification requires
+fn xdg_home(var: &str, fallback: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+}
+
+/// `$XDG_CONFIG_HOME/osutil`
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_home("XDG_CONFIG_HOME", ".config").map(|path| path.join("osutil"))
+}
+
+/// `$XDG_STATE_HOME/osutil`
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_home("XDG_STATE_HOME", ".local/state").map(|path| path.join("osutil"))
+}
+
+/// `name` in [`state_dir`], or an error when there is no state directory
+pub fn state_file(name: &str) -> io::Result<PathBuf> {
+    state_dir().map(|dir| dir.join(name)).ok_or_else(|| {
+        io::Error::new(
+            ErrorKind::NotFound,
+            "neither $XDG_STATE_HOME nor $HOME is set",
+        )
+    })
+}
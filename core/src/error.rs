@@ -0,0 +1,109 @@
+use std::{
+    fmt, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Errors raised while loading tabs and configuration files
+#[derive(Debug)]
+pub enum Error {
+    /// A file could not be read
+    Read { path: PathBuf, source: io::Error },
+    /// A TOML file could not be parsed
+    Parse {
+        path: PathBuf,
+        /// Byte range of the offending TOML, if known
+        span: Option<Range<usize>>,
+        /// One-based line and column of the start of `span`
+        location: Option<(usize, usize)>,
+        source: Box<toml::de::Error>,
+    },
+    /// A file parsed, but holds a value that cannot be used
+    Invalid { path: PathBuf, message: String },
+    /// The built-in tabs could not be extracted to a temporary directory
+    Extract(io::Error),
+    /// No tab could be loaded; holds the errors of the tabs that were skipped
+    NoTabs(Vec<Error>),
+    /// Entries that depend on each other through `requires` or `after`. The first entry is
+    /// repeated at the end.
+    DependencyCycle { cycle: Vec<String> },
+}
+
+impl Error {
+    pub(crate) fn read(path: &Path, source: io::Error) -> Self {
+        Error::Read {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub(crate) fn parse(path: &Path, content: &str, source: toml::de::Error) -> Self {
+        let span = source.span();
+        let location = span.as_ref().map(|span| {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+            (line, column)
+        });
+        Error::Parse {
+            path: path.to_path_buf(),
+            span,
+            location,
+            source: Box::new(source),
+        }
+    }
+
+    /// The file this error relates to, if any
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Read { path, .. } | Error::Parse { path, .. } | Error::Invalid { path, .. } => {
+                Some(path)
+            }
+            Error::Extract(_) | Error::NoTabs(_) | Error::DependencyCycle { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Read { path, source } => {
+                write!(f, "failed to read {}: {source}", path.display())
+            }
+            Error::Parse {
+                path,
+                location,
+                source,
+                ..
+            } => {
+                write!(f, "failed to parse {}", path.display())?;
+                if let Some((line, column)) = location {
+                    write!(f, " at line {line}, column {column}")?;
+                }
+                write!(f, ": {}", source.message())
+            }
+            Error::Invalid { path, message } => write!(f, "invalid {}: {message}", path.display()),
+            Error::Extract(source) => write!(f, "failed to extract the built-in tabs: {source}"),
+            Error::DependencyCycle { cycle } => {
+                write!(f, "entries depend on each other: {}", cycle.join(" → "))
+            }
+            Error::NoTabs(errors) => {
+                f.write_str("no tabs could be loaded")?;
+                for error in errors {
+                    write!(f, "\n  {error}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Read { source, .. } | Error::Extract(source) => Some(source),
+            Error::Parse { source, .. } => Some(source.as_ref()),
+            Error::Invalid { .. } | Error::NoTabs(_) | Error::DependencyCycle { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{HiddenReason, ListNode, Tab};
+use ego_tree::{NodeId, Tree};
+use std::rc::Rc;
+
+impl Tab {
+    /// Hide the entries whose `task_list` has one of `flags`, and show again the entries hidden
+    /// for a flag that is not listed anymore. Directories left without an entry to show are
+    /// hidden as well. Entries hidden by their preconditions stay hidden.
+    pub fn exclude_task_flags(&mut self, flags: &[String]) {
+        let root = self.tree.root().id();
+        exclude_below(&mut self.tree, root, flags);
+    }
+}
+
+fn exclude_below(tree: &mut Tree<Rc<ListNode>>, id: NodeId, flags: &[String]) {
+    let children: Vec<NodeId> = tree.get(id).unwrap().children().map(|c| c.id()).collect();
+    for child in children {
+        exclude_below(tree, child, flags);
+
+        let node = tree.get(child).unwrap();
+        let value = node.value();
+        let hidden = match &value.hidden {
+            None | Some(HiddenReason::NoSupportedEntries) if node.has_children() => node
+                .children()
+                .all(|entry| entry.value().hidden.is_some())
+                .then_some(HiddenReason::NoSupportedEntries),
+            None | Some(HiddenReason::ExcludedFlag { .. }) if !node.has_children() => flags
+                .iter()
+                .find(|flag| value.has_task_flag(flag))
+                .map(|flag| HiddenReason::ExcludedFlag { flag: flag.clone() }),
+            _ => continue,
+        };
+        if hidden != value.hidden {
+            Rc::make_mut(tree.get_mut(child).unwrap().value()).hidden = hidden;
+        }
+    }
+}
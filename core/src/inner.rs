@@ -1,8 +1,13 @@
-use crate::{Command, ListNode, Tab};
+use crate::{
+    Command, Error, Input, InputKind, ListNode, Tab, dependencies,
+    dirs::config_dir,
+    precondition::{HiddenReason, Precondition, PreconditionFailure},
+};
 use ego_tree::{NodeMut, Tree};
 use include_dir::{Dir, include_dir};
 use serde::Deserialize;
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::{BufRead, BufReader, Read},
     ops::{Deref, DerefMut},
@@ -12,11 +17,11 @@ use std::{
 };
 use temp_dir::TempDir;
 
-const TAB_DATA: Dir = include_dir!("$CARGO_MANIFEST_DIR/tabs");
+pub(crate) const TAB_DATA: Dir = include_dir!("$CARGO_MANIFEST_DIR/tabs");
 
 // Allow the unused TempDir to be stored for later destructor call
 #[allow(dead_code)]
-pub struct TabList(pub Vec<Tab>, TempDir);
+pub struct TabList(pub Vec<Tab>, TempDir, Vec<Error>);
 
 // Implement deref to allow Vec<Tab> methods to be called on TabList
 impl Deref for TabList {
@@ -31,6 +36,24 @@ impl DerefMut for TabList {
         &mut self.0
     }
 }
+impl TabList {
+    /// Find a command (leaf entry) by name across all tabs, in tab order
+    pub fn find_command_by_name(&self, name: &str) -> Option<Rc<ListNode>> {
+        self.iter().find_map(|tab| tab.find_command_by_name(name))
+    }
+
+    /// Errors of the tab files that were skipped while loading
+    pub fn errors(&self) -> &[Error] {
+        &self.2
+    }
+
+    /// Tabs built in memory, without scripts extracted anywhere
+    #[cfg(test)]
+    pub(crate) fn from_tabs(tabs: Vec<Tab>) -> Self {
+        TabList(tabs, TempDir::new().unwrap(), Vec::new())
+    }
+}
+
 impl IntoIterator for TabList {
     type Item = Tab;
     type IntoIter = std::vec::IntoIter<Self::Item>;
@@ -40,72 +63,146 @@ impl IntoIterator for TabList {
     }
 }
 
-pub fn get_tabs(validate: bool) -> TabList {
-    let (temp_dir, tab_files) = TabDirectories::get_tabs();
+/// Load the tabs for this platform. When `keep_hidden` is set, entries rejected by their
+/// preconditions stay in the tree with `ListNode::hidden` set instead of being removed.
+///
+/// Tabs from `$XDG_CONFIG_HOME/osutil/tabs` and `user_tab_dirs` are loaded after the built-in
+/// ones. A user tab with the same name as an existing tab appends its entries to that tab.
+///
+/// Entries whose `task_list` has one of `excluded_flags` are hidden like unsupported ones, see
+/// [`Tab::exclude_task_flags`], whether or not `validate` is set.
+///
+/// Tab files that cannot be read or parsed are skipped and reported by [`TabList::errors`], as
+/// are entries that depend on each other through `requires` or `after`.
+pub fn get_tabs(
+    validate: bool,
+    keep_hidden: bool,
+    user_tab_dirs: &[PathBuf],
+    excluded_flags: &[String],
+) -> Result<TabList, Error> {
+    let mut errors = Vec::new();
+    let (temp_dir, tab_files) = TabDirectories::get_tabs(user_tab_dirs, &mut errors)?;
 
     let tabs: Vec<_> = tab_files
         .into_iter()
-        .map(|path| {
+        .filter_map(|path| {
             let directory = path.parent().unwrap().to_owned();
-            let data = std::fs::read_to_string(&path).unwrap_or_else(|e| {
-                panic!("Failed to read tab data at {path:?}: {e}");
-            });
-            let mut tab_data: TabEntry = toml::from_str(&data).expect("Failed to parse tab data");
+            let mut tab_data: TabEntry = match read_toml(&path) {
+                Ok(tab_data) => tab_data,
+                Err(e) => {
+                    errors.push(e);
+                    return None;
+                }
+            };
+            drop_unanswerable_entries(&mut tab_data.data, &path, &mut errors);
 
-            if validate {
+            if validate && !keep_hidden {
                 filter_entries(&mut tab_data.data);
             }
-            (tab_data, directory)
+            Some((tab_data, directory))
         })
         .collect();
 
-    let tabs: Vec<Tab> = tabs
-        .into_iter()
-        .map(|(TabEntry { name, data }, directory)| {
-            let mut tree = Tree::new(Rc::new(ListNode {
-                name: "root".to_string(),
-                description: String::new(),
-                command: Command::None,
-                task_list: String::new(),
-                multi_select: false,
-            }));
-            let mut root = tree.root_mut();
-            create_directory(data, &mut root, &directory, validate, true);
-            Tab { name, tree }
-        })
-        .collect();
+    let mut merged: Vec<Tab> = Vec::new();
+    for (TabEntry { name, data }, directory) in tabs {
+        let tab = match merged.iter().position(|tab| tab.name == name) {
+            Some(index) => &mut merged[index],
+            None => {
+                merged.push(Tab {
+                    name,
+                    tree: Tree::new(Rc::new(ListNode {
+                        name: "root".to_string(),
+                        description: String::new(),
+                        command: Command::None,
+                        task_list: String::new(),
+                        multi_select: false,
+                        hidden: None,
+                        requires: Vec::new(),
+                        after: Vec::new(),
+                        inputs: Vec::new(),
+                    })),
+                });
+                merged.last_mut().unwrap()
+            }
+        };
+        let mut root = tab.tree.root_mut();
+        let check_preconditions = validate && keep_hidden;
+        create_directory(
+            data,
+            &mut root,
+            &directory,
+            validate,
+            check_preconditions,
+            true,
+        );
+    }
+    let mut tabs = merged;
+    for tab in &mut tabs {
+        tab.exclude_task_flags(excluded_flags);
+        if !keep_hidden {
+            let hidden: Vec<_> = tab
+                .tree
+                .root()
+                .descendants()
+                .filter(|node| node.value().hidden.is_some())
+                .map(|node| node.id())
+                .collect();
+            for id in hidden {
+                tab.tree.get_mut(id).unwrap().detach();
+            }
+        }
+    }
 
     if tabs.is_empty() {
-        panic!("No tabs found");
+        return Err(Error::NoTabs(errors));
     }
-    TabList(tabs, temp_dir)
+    errors.extend(dependencies::find_cycles(&tabs));
+    Ok(TabList(tabs, temp_dir, errors))
+}
+
+pub(crate) fn read_toml<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let content = std::fs::read_to_string(path).map_err(|e| Error::read(path, e))?;
+    toml::from_str(&content).map_err(|e| Error::parse(path, &content, e))
 }
 
 #[derive(Deserialize)]
-struct TabDirectories {
-    directories: Vec<PathBuf>,
+pub(crate) struct TabDirectories {
+    pub(crate) directories: Vec<PathBuf>,
 }
 
 #[derive(Deserialize)]
-struct TabEntry {
-    name: String,
-    data: Vec<Entry>,
+pub(crate) struct TabEntry {
+    pub(crate) name: String,
+    pub(crate) data: Vec<Entry>,
 }
 
 #[derive(Deserialize)]
-struct Entry {
-    name: String,
+pub(crate) struct Entry {
+    pub(crate) name: String,
     #[allow(dead_code)]
     #[serde(default)]
     description: String,
     #[serde(default)]
     preconditions: Option<Vec<Precondition>>,
     #[serde(flatten)]
-    entry_type: EntryType,
+    pub(crate) entry_type: EntryType,
     #[serde(default)]
-    task_list: String,
+    pub(crate) task_list: String,
     #[serde(default = "default_true")]
     multi_select: bool,
+    #[serde(default)]
+    pub(crate) requires: Vec<String>,
+    #[serde(default)]
+    pub(crate) after: Vec<String>,
+    /// Environment variables for a script entry
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    /// Arguments passed to a script entry, after the script path
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    /// Values asked for before a script entry runs
+    #[serde(default)]
+    pub(crate) inputs: Vec<Input>,
 }
 
 fn default_true() -> bool {
@@ -114,7 +211,7 @@ fn default_true() -> bool {
 
 #[derive(Deserialize)]
 #[serde(rename_all = "snake_case")]
-enum EntryType {
+pub(crate) enum EntryType {
     Entries(Vec<Entry>),
     Command(String),
     Script(PathBuf),
@@ -122,49 +219,42 @@ enum EntryType {
 
 impl Entry {
     fn is_supported(&self) -> bool {
-        self.preconditions.as_deref().is_none_or(|preconditions| {
-            preconditions.iter().all(
-                |Precondition {
-                     matches,
-                     data,
-                     values,
-                 }| {
-                    match data {
-                        SystemDataType::Environment(var_name) => std::env::var(var_name)
-                            .is_ok_and(|var| values.contains(&var) == *matches),
-                        SystemDataType::ContainingFile(file) => std::fs::read_to_string(file)
-                            .is_ok_and(|data| {
-                                values
-                                    .iter()
-                                    .any(|matching| data.contains(matching) == *matches)
-                            }),
-                        SystemDataType::CommandExists => values
-                            .iter()
-                            .all(|command| which::which(command).is_ok() == *matches),
-                        SystemDataType::FileExists => values.iter().all(|p| Path::new(p).is_file()),
-                    }
-                },
-            )
-        })
+        self.failed_precondition().is_none()
     }
-}
 
-#[derive(Deserialize)]
-struct Precondition {
-    // If true, the data must be contained within the list of values.
-    // Otherwise, the data must not be contained within the list of values
-    matches: bool,
-    data: SystemDataType,
-    values: Vec<String>,
+    /// The first precondition that does not hold on this system, if any
+    fn failed_precondition(&self) -> Option<PreconditionFailure> {
+        self.preconditions
+            .as_deref()?
+            .iter()
+            .enumerate()
+            .find_map(|(index, precondition)| precondition.check(&self.name, index))
+    }
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "snake_case")]
-enum SystemDataType {
-    Environment(String),
-    ContainingFile(PathBuf),
-    FileExists,
-    CommandExists,
+/// Remove the entries with a `choice` input that has no choices, which could never be answered
+fn drop_unanswerable_entries(entries: &mut Vec<Entry>, path: &Path, errors: &mut Vec<Error>) {
+    entries.retain_mut(|entry| {
+        if let EntryType::Entries(entries) = &mut entry.entry_type {
+            drop_unanswerable_entries(entries, path, errors);
+            return true;
+        }
+        let Some(input) = entry
+            .inputs
+            .iter()
+            .find(|input| input.kind == InputKind::Choice && input.choices.is_empty())
+        else {
+            return true;
+        };
+        errors.push(Error::Invalid {
+            path: path.to_path_buf(),
+            message: format!(
+                "skipped {:?}, its choice input {} has no choices",
+                entry.name, input.name
+            ),
+        });
+        false
+    });
 }
 
 fn filter_entries(entries: &mut Vec<Entry>) {
@@ -186,10 +276,19 @@ fn create_directory(
     node: &mut NodeMut<Rc<ListNode>>,
     command_dir: &Path,
     validate: bool,
+    check_preconditions: bool,
     parent_multi_select: bool,
 ) {
+    let parent_hidden = node.value().hidden.clone();
     for entry in data {
         let multi_select = parent_multi_select && entry.multi_select;
+        let hidden = match &parent_hidden {
+            Some(reason) => Some(reason.clone()),
+            None if check_preconditions => {
+                entry.failed_precondition().map(HiddenReason::Precondition)
+            }
+            None => None,
+        };
 
         match entry.entry_type {
             EntryType::Entries(entries) => {
@@ -199,24 +298,50 @@ fn create_directory(
                     command: Command::None,
                     task_list: String::new(),
                     multi_select,
+                    hidden,
+                    requires: Vec::new(),
+                    after: Vec::new(),
+                    inputs: Vec::new(),
                 }));
-                create_directory(entries, &mut node, command_dir, validate, multi_select);
+                create_directory(
+                    entries,
+                    &mut node,
+                    command_dir,
+                    validate,
+                    check_preconditions,
+                    multi_select,
+                );
+                // A directory without any supported entry is hidden as well
+                if check_preconditions
+                    && node.value().hidden.is_none()
+                    && node
+                        .as_ref()
+                        .children()
+                        .all(|child| child.value().hidden.is_some())
+                {
+                    Rc::make_mut(node.value()).hidden = Some(HiddenReason::NoSupportedEntries);
+                }
             }
             EntryType::Command(command) => {
                 node.append(Rc::new(ListNode {
                     name: entry.name,
                     description: entry.description,
                     command: Command::Raw(command),
-                    task_list: String::new(),
+                    task_list: entry.task_list,
                     multi_select,
+                    hidden,
+                    requires: entry.requires,
+                    after: entry.after,
+                    inputs: Vec::new(),
                 }));
             }
             EntryType::Script(script) => {
                 let script_path = command_dir.join(&script).with_extension("sh");
 
                 if script_path.exists()
-                    && let Some((executable, args)) = get_shebang(&script_path, validate)
+                    && let Some((executable, mut args)) = get_shebang(&script_path, validate)
                 {
+                    args.extend(entry.args);
                     node.append(Rc::new(ListNode {
                         name: entry.name,
                         description: entry.description,
@@ -224,9 +349,14 @@ fn create_directory(
                             executable,
                             args,
                             file: script_path,
+                            env: entry.env,
                         },
                         task_list: entry.task_list,
                         multi_select,
+                        hidden,
+                        requires: entry.requires,
+                        after: entry.after,
+                        inputs: entry.inputs,
                     }));
                 }
             }
@@ -282,47 +412,47 @@ fn is_executable(path: &Path) -> bool {
 }
 
 impl TabDirectories {
-    fn get_tabs() -> (TempDir, Vec<PathBuf>) {
-        let temp_dir = TempDir::with_prefix("osutil_scripts").unwrap();
-        TAB_DATA
-            .extract(&temp_dir)
-            .expect("Failed to extract the saved directory");
+    fn get_tabs(
+        user_tab_dirs: &[PathBuf],
+        errors: &mut Vec<Error>,
+    ) -> Result<(TempDir, Vec<PathBuf>), Error> {
+        let temp_dir = TempDir::with_prefix("osutil_scripts").map_err(Error::Extract)?;
+        TAB_DATA.extract(&temp_dir).map_err(Error::Extract)?;
+
+        let mut tab_paths = Self::tab_files(temp_dir.path())?;
+
+        let default_user_dir = config_dir()
+            .map(|dir| dir.join("tabs"))
+            .filter(|dir| dir.is_dir());
+        for dir in default_user_dir.iter().chain(user_tab_dirs) {
+            match Self::tab_files(dir) {
+                Ok(files) => tab_paths.extend(files),
+                Err(e) => errors.push(e),
+            }
+        }
+        Ok((temp_dir, tab_paths))
+    }
 
+    /// Paths of the `tab_data.toml` files listed in the `tabs.toml` of a tabs directory,
+    /// preferring the `tabs.toml` of the current platform's subdirectory when there is one
+    fn tab_files(root: &Path) -> Result<Vec<PathBuf>, Error> {
         // Determine the platform and load the appropriate tabs.toml
         let platform = Self::detect_platform();
-        let platform_tabs_file = temp_dir.path().join(platform).join("tabs.toml");
-        let fallback_tabs_file = temp_dir.path().join("tabs.toml");
-
-        let (tab_files, use_platform_paths) = if platform_tabs_file.exists() {
-            (
-                std::fs::read_to_string(&platform_tabs_file)
-                    .expect("Failed to read platform tabs.toml"),
-                true,
-            )
+        let platform_root = root.join(platform);
+        let platform_tabs_file = platform_root.join("tabs.toml");
+
+        let tabs_root = if platform_tabs_file.exists() {
+            platform_root
         } else {
-            (
-                std::fs::read_to_string(&fallback_tabs_file).expect("Failed to read tabs.toml"),
-                false,
-            )
+            root.to_path_buf()
         };
 
-        let data: Self = toml::from_str(&tab_files).expect("Failed to parse tabs.toml");
-        let tab_paths = data
+        let data: Self = read_toml(&tabs_root.join("tabs.toml"))?;
+        Ok(data
             .directories
             .iter()
-            .map(|path| {
-                if use_platform_paths {
-                    temp_dir
-                        .path()
-                        .join(platform)
-                        .join(path)
-                        .join("tab_data.toml")
-                } else {
-                    temp_dir.path().join(path).join("tab_data.toml")
-                }
-            })
-            .collect();
-        (temp_dir, tab_paths)
+            .map(|path| tabs_root.join(path).join("tab_data.toml"))
+            .collect())
     }
 
     fn detect_platform() -> &'static str {
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Deserializer};
+
+/// A value asked for before a script entry runs, passed to it as an environment variable
+#[derive(Clone, Hash, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Input {
+    /// Name of the environment variable holding the value
+    pub name: String,
+    /// Label shown when asking for the value
+    pub prompt: String,
+    #[serde(default)]
+    pub kind: InputKind,
+    /// Value used unless another one is entered, `"true"` or `"false"` for `bool` inputs
+    #[serde(default, deserialize_with = "deserialize_default")]
+    pub default: Option<String>,
+    /// Values to pick from, for `choice` inputs
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Hash, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputKind {
+    #[default]
+    Text,
+    /// Text that is not shown while it is typed
+    Password,
+    /// One of the input's `choices`
+    Choice,
+    /// `true` or `false`
+    Bool,
+}
+
+impl Input {
+    /// The value to start from: the environment variable when it is set, otherwise the default
+    pub fn initial_value(&self) -> Option<String> {
+        std::env::var(&self.name)
+            .ok()
+            .or_else(|| self.default.clone())
+    }
+}
+
+/// Defaults may be written as booleans for `bool` inputs
+fn deserialize_default<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Default {
+        Bool(bool),
+        Text(String),
+    }
+    Ok(match Default::deserialize(deserializer)? {
+        Default::Bool(value) => Some(value.to_string()),
+        Default::Text(value) => Some(value),
+    })
+}
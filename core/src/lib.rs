@@ -1,14 +1,52 @@
 mod config;
+mod dependencies;
+mod dirs;
+mod error;
+mod exclude;
 mod inner;
+mod input;
+mod lookup;
+mod precondition;
+mod validate;
 
 use std::rc::Rc;
 
 pub use ego_tree;
-use ego_tree::Tree;
-use std::path::PathBuf;
+use ego_tree::{NodeId, Tree};
+use std::{collections::BTreeMap, path::PathBuf};
 
-pub use config::{Config, ConfigValues};
+pub use config::{
+    Config, ConfigSource, ConfigValues, EntryConfig, EntrySettings, FailurePolicy, SYSTEM_CONFIG,
+    UnusedEntrySettings,
+};
+pub use dependencies::UnmetRequirement;
+pub use dirs::{config_dir, state_dir, state_file};
+pub use error::Error;
 pub use inner::{TabList, get_tabs};
+pub use input::{Input, InputKind};
+pub use lookup::{LookupError, resolve_entries};
+pub use precondition::{HiddenReason, PreconditionFailure};
+pub use validate::{Issue, validate_tabs};
+
+/// Codes used in an entry's `task_list`, with what they mean. Any code can be prefixed with
+/// `P` to mark the task as privileged (e.g. `PFM`).
+pub const TASK_FLAGS: &[(&str, &str)] = &[
+    ("D", "disk modifications (ex. partitioning) (privileged)"),
+    ("FI", "flatpak installation"),
+    ("FM", "file modification"),
+    ("I", "installation (privileged)"),
+    ("K", "kernel modifications (privileged)"),
+    ("MP", "package manager actions"),
+    ("SI", "full system installation"),
+    ("SS", "systemd actions (privileged)"),
+    ("RP", "package removal"),
+];
+
+/// Whether `flag` is one of [`TASK_FLAGS`], optionally prefixed with `P`
+pub fn is_known_task_flag(flag: &str) -> bool {
+    let is_code = |code: &str| TASK_FLAGS.iter().any(|(known, _)| *known == code);
+    is_code(flag) || flag.strip_prefix('P').is_some_and(is_code)
+}
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 pub enum Command {
@@ -18,6 +56,8 @@ pub enum Command {
         args: Vec<String>,
         // The file path is included within the arguments; don't pass this in addition
         file: PathBuf,
+        /// Environment variables set for the script, from the entry's `env` table
+        env: BTreeMap<String, String>,
     },
     None, // Directory
 }
@@ -35,13 +75,80 @@ pub struct ListNode {
     pub command: Command,
     pub task_list: String,
     pub multi_select: bool,
+    /// Why the entry is unsupported on this system. Only set when hidden entries are kept
+    pub hidden: Option<HiddenReason>,
+    /// Names of the entries that must run before this one, pulled in when it is selected
+    pub requires: Vec<String>,
+    /// Names of the entries that run before this one when they are selected too
+    pub after: Vec<String>,
+    /// Values asked for before the entry runs
+    pub inputs: Vec<Input>,
+}
+
+impl Command {
+    /// The command with more environment variables set. Only scripts take variables, other
+    /// commands are returned unchanged.
+    pub fn with_env(&self, values: impl IntoIterator<Item = (String, String)>) -> Command {
+        let mut command = self.clone();
+        if let Command::LocalFile { env, .. } = &mut command {
+            env.extend(values);
+        }
+        command
+    }
+}
+
+impl ListNode {
+    /// Whether the entry's `task_list` has `flag`. A flag without the `P` prefix also matches
+    /// its privileged form, so `FM` matches `PFM`.
+    pub fn has_task_flag(&self, flag: &str) -> bool {
+        self.task_list.split_whitespace().any(|code| {
+            code.eq_ignore_ascii_case(flag)
+                || code
+                    .strip_prefix('P')
+                    .is_some_and(|code| code.eq_ignore_ascii_case(flag))
+        })
+    }
+
+    /// The inputs whose variable is not already set by the entry
+    pub fn unanswered_inputs(&self) -> impl Iterator<Item = &Input> {
+        let env = match &self.command {
+            Command::LocalFile { env, .. } => Some(env),
+            _ => None,
+        };
+        self.inputs
+            .iter()
+            .filter(move |input| env.is_none_or(|env| !env.contains_key(&input.name)))
+    }
+
+    /// Names of the variables holding the entry's password inputs
+    pub fn password_inputs(&self) -> impl Iterator<Item = &str> {
+        self.inputs
+            .iter()
+            .filter(|input| input.kind == InputKind::Password)
+            .map(|input| input.name.as_str())
+    }
 }
 
 impl Tab {
+    /// The name of the tab, then the directories leading to the node `id` of this tab and the
+    /// node's own name, as shown in breadcrumbs and kept in the Favorites and Recent tabs
+    pub fn entry_path(&self, id: NodeId) -> Vec<&str> {
+        let node = self.tree.get(id).unwrap();
+        let mut path: Vec<&str> = std::iter::once(node)
+            .chain(node.ancestors())
+            .filter(|node| node.parent().is_some())
+            .map(|node| node.value().name.as_str())
+            .collect();
+        path.push(&self.name);
+        path.reverse();
+        path
+    }
+
     fn find_command_by_name(&self, name: &str) -> Option<Rc<ListNode>> {
         self.tree.root().descendants().find_map(|node| {
             let node_value = node.value();
-            (node_value.name == name && !node.has_children()).then_some(node_value.clone())
+            (node_value.name == name && !node.has_children() && node_value.hidden.is_none())
+                .then_some(node_value.clone())
         })
     }
 }
@@ -0,0 +1,103 @@
+use crate::{Command, HiddenReason, ListNode, Tab, TabList};
+use std::{fmt, rc::Rc};
+
+/// Why a name does not resolve to a single entry that can run
+#[derive(Clone, Debug)]
+pub enum LookupError {
+    /// No entry has this name
+    NotFound(String),
+    /// The entry exists, but not on this system
+    Unavailable {
+        name: String,
+        reason: Box<HiddenReason>,
+    },
+    /// More than one available entry has this name; holds where each of them is
+    Ambiguous {
+        name: String,
+        locations: Vec<String>,
+    },
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LookupError::NotFound(name) => write!(f, "no such entry: {name}"),
+            LookupError::Unavailable { name, reason } => {
+                write!(f, "{name} is not available on this system: {reason}")
+            }
+            LookupError::Ambiguous { name, locations } => {
+                write!(
+                    f,
+                    "{name} is ambiguous, it matches {}",
+                    locations.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl TabList {
+    /// Resolve a name to the entry it refers to. Unlike [`TabList::find_command_by_name`], tells
+    /// apart names that match nothing, entries hidden on this system (when the tabs were loaded
+    /// with hidden entries kept) and names shared by several available entries.
+    pub fn resolve_entry(&self, name: &str) -> Result<Rc<ListNode>, LookupError> {
+        resolve_entry(self, name)
+    }
+
+    /// Resolve each name with [`TabList::resolve_entry`], keeping the order of the names
+    pub fn resolve_entries(&self, names: &[String]) -> (Vec<Rc<ListNode>>, Vec<LookupError>) {
+        resolve_entries(self, names)
+    }
+}
+
+fn resolve_entry(tabs: &[Tab], name: &str) -> Result<Rc<ListNode>, LookupError> {
+    let mut available = Vec::new();
+    let mut hidden = None;
+    for tab in tabs {
+        for node in tab.tree.root().descendants() {
+            let value = node.value();
+            if value.name != name || value.command == Command::None {
+                continue;
+            }
+            match &value.hidden {
+                None => {
+                    let path = tab.entry_path(node.id());
+                    let location = path[..path.len() - 1].join(" › ");
+                    available.push((value.clone(), location));
+                }
+                Some(reason) => {
+                    hidden.get_or_insert_with(|| Box::new(reason.clone()));
+                }
+            }
+        }
+    }
+
+    match (available.len(), hidden) {
+        (1, _) => Ok(available.remove(0).0),
+        (0, Some(reason)) => Err(LookupError::Unavailable {
+            name: name.to_string(),
+            reason,
+        }),
+        (0, None) => Err(LookupError::NotFound(name.to_string())),
+        _ => Err(LookupError::Ambiguous {
+            name: name.to_string(),
+            locations: available
+                .into_iter()
+                .map(|(_, location)| location)
+                .collect(),
+        }),
+    }
+}
+
+/// [`TabList::resolve_entries`] among some of the tabs only
+pub fn resolve_entries(tabs: &[Tab], names: &[String]) -> (Vec<Rc<ListNode>>, Vec<LookupError>) {
+    let mut nodes = Vec::with_capacity(names.len());
+    let mut errors = Vec::new();
+    for name in names {
+        match resolve_entry(tabs, name) {
+            Ok(node) => nodes.push(node),
+            Err(e) => errors.push(e),
+        }
+    }
+    (nodes, errors)
+}
@@ -0,0 +1,686 @@
+use serde::{Deserialize, Deserializer, Serialize, de::Error as _};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    process,
+    sync::OnceLock,
+};
+
+/// A precondition from `tab_data.toml`: either a single check or a group of preconditions.
+/// Groups are written as `any = [...]`, `all = [...]` or `not = {...}` and may be nested.
+pub(crate) enum Precondition {
+    /// Holds when at least one of the preconditions holds
+    Any(Vec<Precondition>),
+    /// Holds when every precondition holds
+    All(Vec<Precondition>),
+    /// Holds when the precondition does not
+    Not(Box<Precondition>),
+    Check(Check),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Group {
+    Any(Vec<Precondition>),
+    All(Vec<Precondition>),
+    Not(Box<Precondition>),
+}
+
+impl<'de> Deserialize<'de> for Precondition {
+    // Deserialized by hand instead of as an untagged enum, so a mistake inside a check is
+    // reported as such rather than as a table that matches no kind of precondition
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = toml::Table::deserialize(deserializer)?;
+        let is_group = table.len() == 1
+            && ["any", "all", "not"]
+                .iter()
+                .any(|key| table.contains_key(*key));
+        let value = toml::Value::Table(table);
+        if !is_group {
+            return value
+                .try_into()
+                .map(Precondition::Check)
+                .map_err(|e: toml::de::Error| D::Error::custom(e.message()));
+        }
+        let group = value
+            .try_into()
+            .map_err(|e: toml::de::Error| D::Error::custom(e.message()))?;
+        Ok(match group {
+            Group::Any(any) => Precondition::Any(any),
+            Group::All(all) => Precondition::All(all),
+            Group::Not(not) => Precondition::Not(not),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Check {
+    // If true, the data must be contained within the list of values.
+    // Otherwise, the data must not be contained within the list of values
+    matches: bool,
+    data: SystemDataType,
+    values: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SystemDataType {
+    Environment(String),
+    ContainingFile(PathBuf),
+    FileExists,
+    CommandExists,
+    /// `ID` from os-release
+    OsReleaseId,
+    /// `ID` and every distribution listed in `ID_LIKE` from os-release
+    OsReleaseIdLike,
+    /// `VERSION_ID` from os-release; values may start with `=`, `!=`, `<`, `<=`, `>` or `>=`
+    VersionId,
+    /// Machine hardware name, as printed by `uname -m`
+    Arch,
+    /// Kernel release, as printed by `uname -r`; values may start with a comparison operator
+    KernelVersion,
+}
+
+/// Why an entry is hidden on this system
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum HiddenReason {
+    /// A precondition of the entry, or of one of its parent directories, does not hold
+    Precondition(PreconditionFailure),
+    /// A directory in which every entry is hidden
+    NoSupportedEntries,
+    /// The entry's `task_list` has a flag excluded by the configuration
+    ExcludedFlag { flag: String },
+}
+
+/// The result of a precondition that did not hold
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize)]
+pub struct PreconditionFailure {
+    /// Name of the entry or directory that declares the precondition
+    pub entry: String,
+    /// Position of the precondition in the entry's `preconditions` list
+    pub index: usize,
+    /// The kind of system data that was checked, as written in `tab_data.toml`
+    pub data: String,
+    /// What the precondition requires
+    pub expected: String,
+    /// What was found on this system
+    pub observed: String,
+}
+
+impl fmt::Display for HiddenReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HiddenReason::Precondition(failure) => failure.fmt(f),
+            HiddenReason::NoSupportedEntries => f.write_str("no supported entries"),
+            HiddenReason::ExcludedFlag { flag } => write!(f, "has the excluded task flag {flag}"),
+        }
+    }
+}
+
+impl fmt::Display for PreconditionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "requires {}, but {}", self.expected, self.observed)
+    }
+}
+
+/// What a precondition that does not hold was looking for, and what it found instead
+struct Unmet {
+    data: String,
+    expected: String,
+    observed: String,
+}
+
+impl Precondition {
+    /// Check the precondition against this system, returning the observation when it fails
+    pub(crate) fn check(&self, entry: &str, index: usize) -> Option<PreconditionFailure> {
+        let Unmet {
+            data,
+            expected,
+            observed,
+        } = self.evaluate().err()?;
+        Some(PreconditionFailure {
+            entry: entry.to_string(),
+            index,
+            data,
+            expected,
+            observed,
+        })
+    }
+
+    fn evaluate(&self) -> Result<(), Unmet> {
+        match self {
+            Precondition::Any(any) => {
+                let mut observed = Vec::new();
+                for precondition in any {
+                    match precondition.evaluate() {
+                        Ok(()) => return Ok(()),
+                        Err(unmet) => observed.push(unmet.observed),
+                    }
+                }
+                Err(Unmet {
+                    data: "any".to_string(),
+                    expected: self.expected(),
+                    observed: if observed.is_empty() {
+                        "the group is empty".to_string()
+                    } else {
+                        observed.join("; ")
+                    },
+                })
+            }
+            Precondition::All(all) => all.iter().try_for_each(Precondition::evaluate),
+            Precondition::Not(not) => match not.evaluate() {
+                Ok(()) => Err(Unmet {
+                    data: "not".to_string(),
+                    expected: self.expected(),
+                    observed: "it holds".to_string(),
+                }),
+                Err(_) => Ok(()),
+            },
+            Precondition::Check(check) => match check.observe() {
+                None => Ok(()),
+                Some(observed) => Err(Unmet {
+                    data: check.data.name().to_string(),
+                    expected: check.expected(),
+                    observed,
+                }),
+            },
+        }
+    }
+
+    fn expected(&self) -> String {
+        let join = |preconditions: &[Precondition], separator: &str| {
+            preconditions
+                .iter()
+                .map(|precondition| match precondition {
+                    Precondition::Check(check) => check.expected(),
+                    group => format!("({})", group.expected()),
+                })
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        match self {
+            Precondition::Any(any) => join(any, " or "),
+            Precondition::All(all) => join(all, " and "),
+            Precondition::Not(not) => format!("not ({})", not.expected()),
+            Precondition::Check(check) => check.expected(),
+        }
+    }
+}
+
+impl Check {
+    /// Returns `None` when the check holds, otherwise a description of what was observed
+    fn observe(&self) -> Option<String> {
+        let Check {
+            matches,
+            data,
+            values,
+        } = self;
+        match data {
+            SystemDataType::Environment(var_name) => match std::env::var(var_name) {
+                Ok(var) if values.contains(&var) == *matches => None,
+                Ok(var) => Some(format!("${var_name} is \"{var}\"")),
+                Err(_) => Some(format!("${var_name} is unset")),
+            },
+            SystemDataType::ContainingFile(file) => {
+                let Ok(data) = std::fs::read_to_string(file) else {
+                    return Some(format!("{} is not readable", file.display()));
+                };
+                if values
+                    .iter()
+                    .any(|matching| data.contains(matching) == *matches)
+                {
+                    None
+                } else if *matches {
+                    Some(format!("{} contains none of them", file.display()))
+                } else {
+                    Some(format!("{} contains all of them", file.display()))
+                }
+            }
+            SystemDataType::CommandExists => {
+                let unexpected: Vec<&str> = values
+                    .iter()
+                    .filter(|command| which::which(command).is_ok() != *matches)
+                    .map(String::as_str)
+                    .collect();
+                match (unexpected.is_empty(), *matches) {
+                    (true, _) => None,
+                    (false, true) => Some(format!("not found: {}", unexpected.join(", "))),
+                    (false, false) => Some(format!("found: {}", unexpected.join(", "))),
+                }
+            }
+            SystemDataType::FileExists => {
+                let unexpected: Vec<&str> = values
+                    .iter()
+                    .filter(|p| Path::new(p).is_file() != *matches)
+                    .map(String::as_str)
+                    .collect();
+                match (unexpected.is_empty(), *matches) {
+                    (true, _) => None,
+                    (false, true) => Some(format!("missing: {}", unexpected.join(", "))),
+                    (false, false) => Some(format!("found: {}", unexpected.join(", "))),
+                }
+            }
+            SystemDataType::OsReleaseId => {
+                let Some(id) = os_release("ID") else {
+                    return Some("os-release has no ID".to_string());
+                };
+                (values.iter().any(|value| value == id) != *matches)
+                    .then(|| format!("ID is \"{id}\""))
+            }
+            SystemDataType::OsReleaseIdLike => {
+                let Some(id) = os_release("ID") else {
+                    return Some("os-release has no ID".to_string());
+                };
+                let id_like = os_release("ID_LIKE").unwrap_or_default();
+                let found = values.iter().any(|value| {
+                    value == id || id_like.split_whitespace().any(|like| like == value)
+                });
+                (found != *matches).then(|| format!("ID is \"{id}\", ID_LIKE is \"{id_like}\""))
+            }
+            SystemDataType::VersionId => {
+                let Some(version) = os_release("VERSION_ID") else {
+                    return Some("os-release has no VERSION_ID".to_string());
+                };
+                (values
+                    .iter()
+                    .any(|requirement| version_matches(version, requirement))
+                    != *matches)
+                    .then(|| format!("VERSION_ID is \"{version}\""))
+            }
+            SystemDataType::Arch => {
+                let Some(arch) = uname_machine() else {
+                    return Some("the architecture could not be determined".to_string());
+                };
+                (values.iter().any(|value| value == arch) != *matches)
+                    .then(|| format!("architecture is \"{arch}\""))
+            }
+            SystemDataType::KernelVersion => {
+                let Some(release) = uname_release() else {
+                    return Some("the kernel version could not be determined".to_string());
+                };
+                (values
+                    .iter()
+                    .any(|requirement| version_matches(release, requirement))
+                    != *matches)
+                    .then(|| format!("kernel version is \"{release}\""))
+            }
+        }
+    }
+
+    fn expected(&self) -> String {
+        let values = self.values.join(", ");
+        match (&self.data, self.matches) {
+            (SystemDataType::Environment(var_name), true) => {
+                format!("${var_name} to be one of [{values}]")
+            }
+            (SystemDataType::Environment(var_name), false) => {
+                format!("${var_name} to be set and not one of [{values}]")
+            }
+            (SystemDataType::ContainingFile(file), true) => {
+                format!("{} to contain one of [{values}]", file.display())
+            }
+            (SystemDataType::ContainingFile(file), false) => {
+                format!("{} to lack one of [{values}]", file.display())
+            }
+            (SystemDataType::CommandExists, true) => format!("commands: {values}"),
+            (SystemDataType::CommandExists, false) => format!("none of the commands: {values}"),
+            (SystemDataType::FileExists, true) => format!("files: {values}"),
+            (SystemDataType::FileExists, false) => format!("none of the files: {values}"),
+            (SystemDataType::OsReleaseId, true) => format!("os-release ID to be one of [{values}]"),
+            (SystemDataType::OsReleaseId, false) => {
+                format!("os-release ID to not be one of [{values}]")
+            }
+            (SystemDataType::OsReleaseIdLike, true) => {
+                format!("os-release ID or ID_LIKE to include one of [{values}]")
+            }
+            (SystemDataType::OsReleaseIdLike, false) => {
+                format!("os-release ID and ID_LIKE to include none of [{values}]")
+            }
+            (SystemDataType::VersionId, true) => {
+                format!("os-release VERSION_ID to satisfy one of [{values}]")
+            }
+            (SystemDataType::VersionId, false) => {
+                format!("os-release VERSION_ID to satisfy none of [{values}]")
+            }
+            (SystemDataType::Arch, true) => format!("architecture to be one of [{values}]"),
+            (SystemDataType::Arch, false) => format!("architecture to not be one of [{values}]"),
+            (SystemDataType::KernelVersion, true) => {
+                format!("kernel version to satisfy one of [{values}]")
+            }
+            (SystemDataType::KernelVersion, false) => {
+                format!("kernel version to satisfy none of [{values}]")
+            }
+        }
+    }
+}
+
+impl SystemDataType {
+    fn name(&self) -> &'static str {
+        match self {
+            SystemDataType::Environment(_) => "environment",
+            SystemDataType::ContainingFile(_) => "containing_file",
+            SystemDataType::FileExists => "file_exists",
+            SystemDataType::CommandExists => "command_exists",
+            SystemDataType::OsReleaseId => "os_release_id",
+            SystemDataType::OsReleaseIdLike => "os_release_id_like",
+            SystemDataType::VersionId => "version_id",
+            SystemDataType::Arch => "arch",
+            SystemDataType::KernelVersion => "kernel_version",
+        }
+    }
+}
+
+/// Look up a key of `/etc/os-release` (or `/usr/lib/os-release`), with quotes removed
+fn os_release(key: &str) -> Option<&'static str> {
+    static OS_RELEASE: OnceLock<HashMap<String, String>> = OnceLock::new();
+    OS_RELEASE
+        .get_or_init(|| {
+            let content = std::fs::read_to_string("/etc/os-release")
+                .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+                .unwrap_or_default();
+            parse_os_release(&content)
+        })
+        .get(key)
+        .map(String::as_str)
+}
+
+/// The `KEY=value` lines of an os-release file, with quotes removed from the values
+fn parse_os_release(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = value.trim();
+            let unquoted = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .or_else(|| {
+                    value
+                        .strip_prefix('\'')
+                        .and_then(|value| value.strip_suffix('\''))
+                })
+                .unwrap_or(value);
+            (key.trim().to_string(), unquoted.to_string())
+        })
+        .collect()
+}
+
+fn uname_machine() -> Option<&'static str> {
+    static MACHINE: OnceLock<Option<String>> = OnceLock::new();
+    MACHINE.get_or_init(|| uname("-m")).as_deref()
+}
+
+fn uname_release() -> Option<&'static str> {
+    static RELEASE: OnceLock<Option<String>> = OnceLock::new();
+    RELEASE.get_or_init(|| uname("-r")).as_deref()
+}
+
+fn uname(flag: &str) -> Option<String> {
+    let output = process::Command::new("uname").arg(flag).output().ok()?;
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// Check a version against a requirement such as `>=22.04`. Without an operator the versions
+/// must be equal.
+fn version_matches(version: &str, requirement: &str) -> bool {
+    let requirement = requirement.trim();
+    let (operator, wanted) = ["<=", ">=", "!=", "==", "<", ">", "="]
+        .iter()
+        .find_map(|operator| {
+            requirement
+                .strip_prefix(operator)
+                .map(|wanted| (*operator, wanted.trim()))
+        })
+        .unwrap_or(("=", requirement));
+
+    let ordering = compare_versions(version, wanted);
+    match operator {
+        "<=" => ordering.is_le(),
+        ">=" => ordering.is_ge(),
+        "<" => ordering.is_lt(),
+        ">" => ordering.is_gt(),
+        "!=" => ordering.is_ne(),
+        _ => ordering.is_eq(),
+    }
+}
+
+/// Compare the leading dotted numeric parts of two versions, so `6.8.0-45-generic` compares
+/// as `6.8.0` and missing components count as zero
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn components(version: &str) -> Vec<u64> {
+        version
+            .split(|c: char| c != '.' && !c.is_ascii_digit())
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+
+    let (a, b) = (components(a), components(b));
+    (0..a.len().max(b.len()))
+        .map(|index| {
+            let left = a.get(index).copied().unwrap_or(0);
+            let right = b.get(index).copied().unwrap_or(0);
+            left.cmp(&right)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Precondition, compare_versions, parse_os_release, version_matches};
+    use serde::Deserialize;
+    use std::cmp::Ordering;
+
+    #[derive(Deserialize)]
+    struct Entry {
+        preconditions: Vec<Precondition>,
+    }
+
+    const PRESENT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    const MISSING: &str = "/nonexistent/osutil-test";
+
+    /// A `file_exists` check on `path`, which holds when the file is there
+    fn file_check(path: &str) -> String {
+        format!("{{ matches = true, data = \"file_exists\", values = [\"{path}\"] }}")
+    }
+
+    fn parse(preconditions: &str) -> Result<Vec<Precondition>, toml::de::Error> {
+        toml::from_str::<Entry>(&format!("preconditions = [{preconditions}]"))
+            .map(|entry| entry.preconditions)
+    }
+
+    fn parse_one(precondition: &str) -> Precondition {
+        parse(precondition).unwrap().remove(0)
+    }
+
+    #[test]
+    fn versions_compare_by_numeric_components() {
+        assert_eq!(compare_versions("22.04", "22.04"), Ordering::Equal);
+        assert_eq!(compare_versions("22.10", "22.04"), Ordering::Greater);
+        assert_eq!(compare_versions("9", "10"), Ordering::Less);
+    }
+
+    #[test]
+    fn missing_components_count_as_zero() {
+        assert_eq!(compare_versions("22.04", "22.04.1"), Ordering::Less);
+        assert_eq!(compare_versions("22.04.0", "22.04"), Ordering::Equal);
+        assert_eq!(compare_versions("6", "6.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn kernel_suffixes_are_ignored() {
+        assert_eq!(compare_versions("6.8.0-45-generic", "6.8"), Ordering::Equal);
+        assert!(version_matches("6.8.0-45-generic", ">=6.1"));
+        assert!(version_matches("6.1.12+rpt-rpi-v8", "<6.2"));
+    }
+
+    #[test]
+    fn every_operator_is_applied() {
+        assert!(version_matches("22.04", "22.04"));
+        assert!(version_matches("22.04", "=22.04"));
+        assert!(version_matches("22.04", "==22.04"));
+        assert!(!version_matches("22.04", "!=22.04"));
+        assert!(version_matches("22.04", "!=24.04"));
+        assert!(version_matches("22.04", "<24.04"));
+        assert!(!version_matches("22.04", "<22.04"));
+        assert!(version_matches("22.04", "<=22.04"));
+        assert!(version_matches("24.04", ">22.04"));
+        assert!(!version_matches("22.04", ">22.04"));
+        assert!(version_matches("22.04", ">=22.04"));
+    }
+
+    #[test]
+    fn requirements_may_have_spaces() {
+        assert!(version_matches("40", " >= 39 "));
+        assert!(!version_matches("38", ">= 39"));
+    }
+
+    #[test]
+    fn os_release_values_are_unquoted() {
+        let os_release = parse_os_release(
+            "# comment\n\
+             NAME=\"Ubuntu\"\n\
+             ID=ubuntu\n\
+             ID_LIKE='debian'\n\
+             VERSION_ID=\"22.04\"\n\
+             \n\
+             PRETTY_NAME=\"Ubuntu 22.04.4 LTS\"\n",
+        );
+        assert_eq!(os_release["NAME"], "Ubuntu");
+        assert_eq!(os_release["ID"], "ubuntu");
+        assert_eq!(os_release["ID_LIKE"], "debian");
+        assert_eq!(os_release["VERSION_ID"], "22.04");
+        assert_eq!(os_release["PRETTY_NAME"], "Ubuntu 22.04.4 LTS");
+        assert!(!os_release.contains_key("# comment"));
+        assert_eq!(os_release.len(), 5);
+    }
+
+    #[test]
+    fn os_release_values_may_contain_equals_signs() {
+        let os_release = parse_os_release("BUG_REPORT_URL=\"https://example.org/?a=b\"\n");
+        assert_eq!(os_release["BUG_REPORT_URL"], "https://example.org/?a=b");
+    }
+
+    #[test]
+    fn checks_and_groups_are_told_apart() {
+        assert!(matches!(
+            parse_one(&file_check(PRESENT)),
+            Precondition::Check(_)
+        ));
+        let group = parse_one(&format!(
+            "{{ any = [{}, {{ not = {} }}] }}",
+            file_check(MISSING),
+            file_check(MISSING)
+        ));
+        let Precondition::Any(any) = group else {
+            panic!("expected an any group");
+        };
+        assert!(matches!(any[0], Precondition::Check(_)));
+        assert!(
+            matches!(&any[1], Precondition::Not(not) if matches!(**not, Precondition::Check(_)))
+        );
+        assert!(matches!(parse_one("{ all = [] }"), Precondition::All(all) if all.is_empty()));
+    }
+
+    #[test]
+    fn mistakes_inside_a_check_are_reported_as_such() {
+        let error = parse("{ matches = true, data = \"file_exists\", valuez = [] }")
+            .err()
+            .unwrap();
+        assert!(error.message().contains("valuez"), "{error}");
+
+        // A group key next to other keys makes the table a check, which cannot have it
+        let error = parse(&format!(
+            "{{ any = [{}], matches = true }}",
+            file_check(PRESENT)
+        ))
+        .err()
+        .unwrap();
+        assert!(error.message().contains("any"), "{error}");
+    }
+
+    #[test]
+    fn single_checks_report_what_they_observed() {
+        assert_eq!(parse_one(&file_check(PRESENT)).check("Entry", 0), None);
+        let failure = parse_one(&file_check(MISSING)).check("Entry", 2).unwrap();
+        assert_eq!(failure.entry, "Entry");
+        assert_eq!(failure.index, 2);
+        assert_eq!(failure.data, "file_exists");
+        assert_eq!(failure.expected, format!("files: {MISSING}"));
+        assert_eq!(failure.observed, format!("missing: {MISSING}"));
+    }
+
+    #[test]
+    fn any_holds_when_one_member_holds() {
+        let any = |members: &[&str]| {
+            let members: Vec<String> = members.iter().map(|path| file_check(path)).collect();
+            parse_one(&format!("{{ any = [{}] }}", members.join(", ")))
+        };
+        assert_eq!(any(&[MISSING, PRESENT]).check("Entry", 0), None);
+
+        let failure = any(&[MISSING, MISSING]).check("Entry", 0).unwrap();
+        assert_eq!(failure.data, "any");
+        assert_eq!(
+            failure.expected,
+            format!("files: {MISSING} or files: {MISSING}")
+        );
+        assert_eq!(
+            failure.observed,
+            format!("missing: {MISSING}; missing: {MISSING}")
+        );
+
+        let failure = parse_one("{ any = [] }").check("Entry", 0).unwrap();
+        assert_eq!(failure.observed, "the group is empty");
+    }
+
+    #[test]
+    fn all_reports_its_first_failing_member() {
+        assert_eq!(parse_one("{ all = [] }").check("Entry", 0), None);
+        let all = parse_one(&format!(
+            "{{ all = [{}, {}, {}] }}",
+            file_check(PRESENT),
+            file_check(MISSING),
+            file_check("/nonexistent/other")
+        ));
+        let failure = all.check("Entry", 0).unwrap();
+        assert_eq!(failure.data, "file_exists");
+        assert_eq!(failure.observed, format!("missing: {MISSING}"));
+    }
+
+    #[test]
+    fn not_reports_that_its_member_holds() {
+        let not = |path: &str| parse_one(&format!("{{ not = {} }}", file_check(path)));
+        assert_eq!(not(MISSING).check("Entry", 0), None);
+        let failure = not(PRESENT).check("Entry", 0).unwrap();
+        assert_eq!(failure.data, "not");
+        assert_eq!(failure.expected, format!("not (files: {PRESENT})"));
+        assert_eq!(failure.observed, "it holds");
+    }
+
+    #[test]
+    fn groups_nest() {
+        // not (any (missing, all (present, not missing))): the any holds, so the not fails
+        let nested = parse_one(&format!(
+            "{{ not = {{ any = [{}, {{ all = [{}, {{ not = {} }}] }}] }} }}",
+            file_check(MISSING),
+            file_check(PRESENT),
+            file_check(MISSING)
+        ));
+        let failure = nested.check("Entry", 0).unwrap();
+        assert_eq!(
+            failure.expected,
+            format!("not (files: {MISSING} or (files: {PRESENT} and (not (files: {MISSING}))))")
+        );
+        assert_eq!(failure.observed, "it holds");
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    Error, Input, InputKind,
+    inner::{Entry, EntryType, TAB_DATA, TabDirectories, TabEntry, read_toml},
+    is_known_task_flag,
+};
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+use temp_dir::TempDir;
+
+/// A problem found in a tabs directory by [`validate_tabs`]
+pub struct Issue {
+    /// The `tabs.toml` or `tab_data.toml` file, relative to the validated directory
+    pub file: PathBuf,
+    /// Names leading to the offending entry, starting with the tab name
+    pub entry: Vec<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.file.display())?;
+        if !self.entry.is_empty() {
+            write!(f, "{}: ", self.entry.join(" › "))?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Check every platform's tabs in `root` (the built-in tabs when `None`) without evaluating
+/// preconditions. Reports unreadable files, empty directories, names used twice in the same
+/// directory, unknown `task_list` flags, missing scripts, malformed shebangs, `env`, `args` and
+/// `inputs` outside script entries, invalid variable names, inconsistent inputs, and `requires`
+/// or `after` naming an entry that does not exist on the same platform.
+pub fn validate_tabs(root: Option<&Path>) -> Result<Vec<Issue>, Error> {
+    let temp_dir;
+    let root = match root {
+        Some(root) => root,
+        None => {
+            temp_dir = TempDir::with_prefix("osutil_validate").map_err(Error::Extract)?;
+            TAB_DATA.extract(temp_dir.path()).map_err(Error::Extract)?;
+            temp_dir.path()
+        }
+    };
+
+    let mut validator = Validator {
+        root,
+        issues: Vec::new(),
+        entry_names: HashSet::new(),
+        dependencies: Vec::new(),
+    };
+    let top_level: TabDirectories = read_toml(&root.join("tabs.toml"))?;
+    for directory in top_level.directories {
+        let directory = root.join(directory);
+        // A directory with its own tabs.toml holds the tabs of one platform
+        let platform_tabs = directory.join("tabs.toml");
+        if platform_tabs.is_file() {
+            match read_toml::<TabDirectories>(&platform_tabs) {
+                Ok(platform) => {
+                    for tab in platform.directories {
+                        validator.check_tab(&directory.join(tab).join("tab_data.toml"));
+                    }
+                }
+                Err(e) => validator.load_error(&platform_tabs, &e),
+            }
+        } else {
+            validator.check_tab(&directory.join("tab_data.toml"));
+        }
+        validator.check_dependencies();
+    }
+    Ok(validator.issues)
+}
+
+struct Validator<'a> {
+    root: &'a Path,
+    issues: Vec<Issue>,
+    /// Names of the runnable entries of the platform being checked
+    entry_names: HashSet<String>,
+    /// `requires` and `after` references of the platform being checked, resolved once all of
+    /// its tabs are read
+    dependencies: Vec<Dependency>,
+}
+
+struct Dependency {
+    file: PathBuf,
+    entry: Vec<String>,
+    key: &'static str,
+    name: String,
+}
+
+impl Validator<'_> {
+    fn check_tab(&mut self, tab_file: &Path) {
+        let tab: TabEntry = match read_toml(tab_file) {
+            Ok(tab) => tab,
+            Err(e) => return self.load_error(tab_file, &e),
+        };
+        let directory = tab_file.parent().unwrap();
+        self.check_entries(tab_file, directory, &[tab.name], &tab.data);
+    }
+
+    fn check_entries(&mut self, file: &Path, directory: &Path, path: &[String], entries: &[Entry]) {
+        let mut names = HashSet::new();
+        for entry in entries {
+            let mut entry_path = path.to_vec();
+            entry_path.push(entry.name.clone());
+
+            if !names.insert(entry.name.as_str()) {
+                self.report(
+                    file,
+                    &entry_path,
+                    "name is used more than once in this directory",
+                );
+            }
+
+            for flag in entry.task_list.split_whitespace() {
+                if !is_known_task_flag(flag) {
+                    self.report(
+                        file,
+                        &entry_path,
+                        format!("unknown task_list flag {flag:?}"),
+                    );
+                }
+            }
+
+            if let EntryType::Entries(_) = entry.entry_type {
+                if !entry.requires.is_empty() || !entry.after.is_empty() {
+                    self.report(
+                        file,
+                        &entry_path,
+                        "requires and after have no effect on a directory",
+                    );
+                }
+            } else {
+                self.entry_names.insert(entry.name.clone());
+                let references = (entry.requires.iter().map(|name| ("requires", name)))
+                    .chain(entry.after.iter().map(|name| ("after", name)));
+                for (key, name) in references {
+                    self.dependencies.push(Dependency {
+                        file: file.to_path_buf(),
+                        entry: entry_path.clone(),
+                        key,
+                        name: name.clone(),
+                    });
+                }
+            }
+
+            if !matches!(entry.entry_type, EntryType::Script(_))
+                && (!entry.env.is_empty() || !entry.args.is_empty() || !entry.inputs.is_empty())
+            {
+                self.report(
+                    file,
+                    &entry_path,
+                    "env, args and inputs only apply to scripts",
+                );
+            }
+            for key in entry.env.keys().filter(|key| !is_variable_name(key)) {
+                self.report(
+                    file,
+                    &entry_path,
+                    format!("{key:?} is not a valid environment variable name"),
+                );
+            }
+
+            let mut input_names = HashSet::new();
+            for input in &entry.inputs {
+                if !input_names.insert(input.name.as_str()) {
+                    self.report(
+                        file,
+                        &entry_path,
+                        format!("input {:?} is declared more than once", input.name),
+                    );
+                }
+                if let Some(problem) = input_problem(input) {
+                    self.report(
+                        file,
+                        &entry_path,
+                        format!("input {:?}: {problem}", input.name),
+                    );
+                }
+            }
+
+            match &entry.entry_type {
+                EntryType::Entries(children) if children.is_empty() => {
+                    self.report(file, &entry_path, "directory has no entries");
+                }
+                EntryType::Entries(children) => {
+                    self.check_entries(file, directory, &entry_path, children);
+                }
+                EntryType::Command(_) => {}
+                EntryType::Script(script) => {
+                    let script_path = directory.join(script).with_extension("sh");
+                    if !script_path.is_file() {
+                        let script = self.relative(&script_path).display().to_string();
+                        self.report(file, &entry_path, format!("missing script {script}"));
+                    } else if let Some(problem) = shebang_problem(&script_path) {
+                        self.report(file, &entry_path, problem);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_dependencies(&mut self) {
+        for dependency in std::mem::take(&mut self.dependencies) {
+            if !self.entry_names.contains(&dependency.name) {
+                self.report(
+                    &dependency.file,
+                    &dependency.entry,
+                    format!("{} unknown entry {:?}", dependency.key, dependency.name),
+                );
+            }
+        }
+        self.entry_names.clear();
+    }
+
+    fn load_error(&mut self, file: &Path, error: &Error) {
+        let message = match error {
+            Error::Read { source, .. } => source.to_string(),
+            Error::Parse {
+                location: Some((line, column)),
+                source,
+                ..
+            } => format!("line {line}, column {column}: {}", source.message()),
+            Error::Parse { source, .. } => source.message().to_string(),
+            other => other.to_string(),
+        };
+        self.report(file, &[], message);
+    }
+
+    fn report(&mut self, file: &Path, entry: &[String], message: impl Into<String>) {
+        self.issues.push(Issue {
+            file: self.relative(file).to_path_buf(),
+            entry: entry.to_vec(),
+            message: message.into(),
+        });
+    }
+
+    fn relative<'p>(&self, path: &'p Path) -> &'p Path {
+        path.strip_prefix(self.root).unwrap_or(path)
+    }
+}
+
+fn input_problem(input: &Input) -> Option<String> {
+    if !is_variable_name(&input.name) {
+        return Some("name is not a valid environment variable name".to_string());
+    }
+    let default = input.default.as_deref();
+    match input.kind {
+        InputKind::Choice if input.choices.is_empty() => Some("has no choices".to_string()),
+        InputKind::Choice => default
+            .filter(|default| !input.choices.iter().any(|choice| choice == default))
+            .map(|default| format!("default {default:?} is not one of the choices")),
+        _ if !input.choices.is_empty() => Some("choices only apply to choice inputs".to_string()),
+        InputKind::Bool => default
+            .filter(|default| !matches!(*default, "true" | "false"))
+            .map(|default| format!("default {default:?} is not true or false")),
+        InputKind::Text | InputKind::Password => None,
+    }
+}
+
+/// Whether `key` can be assigned in `sh`: a letter or underscore, then letters, digits and
+/// underscores
+pub(crate) fn is_variable_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Scripts without a shebang run with `/bin/sh -e`; a shebang must name an absolute interpreter
+fn shebang_problem(script_path: &Path) -> Option<String> {
+    let file = match File::open(script_path) {
+        Ok(file) => file,
+        Err(e) => return Some(format!("cannot read script: {e}")),
+    };
+    let mut first_line = String::new();
+    if let Err(e) = BufReader::new(file).read_line(&mut first_line) {
+        return Some(format!("cannot read script: {e}"));
+    }
+    let shebang = first_line.strip_prefix("#!")?;
+    match shebang.split_whitespace().next() {
+        None => Some("shebang has no interpreter".to_string()),
+        Some(interpreter) if !Path::new(interpreter).is_absolute() => Some(format!(
+            "shebang interpreter {interpreter:?} is not an absolute path"
+        )),
+        Some(_) => None,
+    }
+}
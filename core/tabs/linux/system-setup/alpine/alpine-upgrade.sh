@@ -6,11 +6,18 @@ upgradeAlpine() {
     printf "%b\n" "${YELLOW}Backing up repositories file...${RC}"
     "$ESCALATION_TOOL" cp /etc/apk/repositories /etc/apk/repositories.backup
 
-    printf "%b\n" "${YELLOW}Choose Alpine version:${RC}"
-    printf "%b\n" "${CYAN}1) Latest Stable${RC}"
-    printf "%b\n" "${CYAN}2) Edge (rolling)${RC}"
-    printf "%b" "Enter your choice (1 or 2): "
-    read -r choice
+    # The version can be passed as the first argument, otherwise it is asked for
+    case "${1:-}" in
+        stable) choice=1 ;;
+        edge) choice=2 ;;
+        *)
+            printf "%b\n" "${YELLOW}Choose Alpine version:${RC}"
+            printf "%b\n" "${CYAN}1) Latest Stable${RC}"
+            printf "%b\n" "${CYAN}2) Edge (rolling)${RC}"
+            printf "%b" "Enter your choice (1 or 2): "
+            read -r choice
+            ;;
+    esac
 
     case $choice in
         1)
@@ -45,4 +52,4 @@ EOF'
 }
 
 checkEnv
-upgradeAlpine 
\ No newline at end of file
+upgradeAlpine "$@" 
\ No newline at end of file
@@ -8,22 +8,28 @@ createUser() {
     clear
     printf "%b\n" "${YELLOW}Create a new user${RC}"
     printf "%b\n" "${YELLOW}=================${RC}"
-    printf "%b" "${YELLOW}Enter the username: ${RC}"
-    read -r username
+    username="${NEW_USERNAME:-}"
+    if [ -z "$username" ]; then
+        printf "%b" "${YELLOW}Enter the username: ${RC}"
+        read -r username
+    fi
 
     if ! echo "$username" | grep '^[a-zA-Z]*$' > /dev/null; then
         printf "%b\n" "${RED}Username must only contain letters and cannot contain spaces.${RC}"
         exit 1
     fi
 
-    printf "%b" "${YELLOW}Enter the password: ${RC}"
-    read -r password
-    printf "%b" "${YELLOW}Enter the password again: ${RC}"
-    read -r password_confirmation
-
-    if [ "$password" != "$password_confirmation" ]; then
-        printf "%b\n" "${RED}Passwords do not match${RC}"
-        exit 1
+    password="${NEW_PASSWORD:-}"
+    if [ -z "$password" ]; then
+        printf "%b" "${YELLOW}Enter the password: ${RC}"
+        read -r password
+        printf "%b" "${YELLOW}Enter the password again: ${RC}"
+        read -r password_confirmation
+
+        if [ "$password" != "$password_confirmation" ]; then
+            printf "%b\n" "${RED}Passwords do not match${RC}"
+            exit 1
+        fi
     fi
 
     "$ESCALATION_TOOL" useradd -m "$username" -g users -s /bin/bash
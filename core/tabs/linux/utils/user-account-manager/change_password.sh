@@ -9,16 +9,28 @@ changePassword() {
     printf "%b\n" "${YELLOW}Change password${RC}"
     printf "%b\n" "${YELLOW}=================${RC}"
 
-    printf "%b" "${YELLOW}Enter the username: ${RC}"
-    read -r username
+    # Values passed in the environment were already confirmed when the entry was started
+    asked=false
+    username="${PASSWORD_USERNAME:-}"
+    if [ -z "$username" ]; then
+        printf "%b" "${YELLOW}Enter the username: ${RC}"
+        read -r username
+        asked=true
+    fi
 
     if id "$username" > /dev/null 2>&1; then
-        printf "%b" "${YELLOW}Enter new password: ${RC}"
-        read -r password
-
-        printf "%b" "${YELLOW}Are you sure you want to change password for ""$username""? [Y/n]: ${RC}"
-        read -r _
-        confirmAction || exit 1
+        password="${NEW_PASSWORD:-}"
+        if [ -z "$password" ]; then
+            printf "%b" "${YELLOW}Enter new password: ${RC}"
+            read -r password
+            asked=true
+        fi
+
+        if [ "$asked" = true ]; then
+            printf "%b" "${YELLOW}Are you sure you want to change password for ""$username""? [y/N]: ${RC}"
+            read -r confirm
+            confirmAction || exit 1
+        fi
 
         echo "$username:$password" | "$ESCALATION_TOOL" chpasswd
         printf "%b\n" "${GREEN}Password changed successfully${RC}"
@@ -1,10 +1,11 @@
 use crate::theme::Theme;
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use osutil_core::{Config, Error, TASK_FLAGS, TabList};
 use std::path::PathBuf;
 
 #[derive(Debug, Parser, Clone)]
 pub struct Args {
-    /// Path to the configuration file
+    /// Path to a configuration file, read after the system and user configuration files
     #[arg(short, long)]
     pub config: Option<PathBuf>,
 
@@ -17,6 +18,10 @@ pub struct Args {
     #[arg(short = 'y', long)]
     pub skip_confirmation: bool,
 
+    /// Show the script that would run instead of running it
+    #[arg(long)]
+    pub dry_run: bool,
+
     /// Show all available options, disregarding compatibility checks (UNSAFE)
     #[arg(short = 'u', long)]
     pub override_validation: bool,
@@ -32,4 +37,104 @@ pub struct Args {
     /// Bypass root user check
     #[arg(short = 'r', long)]
     pub bypass_root: bool,
+
+    /// Load additional tabs from this directory (can be repeated)
+    #[arg(long = "tabs-dir", value_name = "DIR")]
+    pub tabs_dirs: Vec<PathBuf>,
+
+    /// Hide entries whose task list has any of these flags, e.g. `D,K,SI`
+    #[arg(long, value_name = "FLAGS", value_delimiter = ',', value_parser = parse_task_flag)]
+    pub exclude_flags: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum Commands {
+    /// Run entries by name without starting the TUI, exiting with the script's exit code
+    Run {
+        /// Names of the entries to run, in order
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+    /// Print the tab tree resolved for this system
+    List {
+        /// Print the entries as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Include entries hidden by precondition checks, with the reason they are hidden
+        #[arg(long)]
+        all: bool,
+    },
+    /// Check tab definitions and scripts for every platform, exiting non-zero on problems
+    Validate {
+        /// Tabs directory to check (defaults to the built-in tabs)
+        path: Option<PathBuf>,
+    },
+    /// Explain whether an entry is available on this system, and which precondition hides it
+    Explain {
+        /// Name of the entry
+        name: String,
+    },
+    /// Run the entries of a recorded run again, without starting the TUI
+    Rerun {
+        /// Id of the run to repeat (defaults to the most recent run)
+        id: Option<u64>,
+    },
+    /// List the recorded runs, or print the log of one of them
+    History {
+        /// Id of the run whose log to print
+        id: Option<u64>,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum ConfigAction {
+    /// Print the effective configuration, with where each value was set
+    Show,
+}
+
+impl Args {
+    /// Read the system and user configuration files, then the one passed with `--config`, each
+    /// overriding the one before, and apply the command line flags on top. Files that cannot be
+    /// read are skipped and their errors returned.
+    pub fn read_config(&self) -> (Config, Vec<Error>) {
+        let mut files = Config::default_files();
+        files.extend(self.config.iter().cloned());
+        let (mut config, errors) = Config::read_layered(&files);
+        config.apply_command_line(
+            self.skip_confirmation,
+            self.size_bypass,
+            &self.tabs_dirs,
+            &self.exclude_flags,
+        );
+        (config, errors)
+    }
+
+    /// Load the tabs, including the tab directories from the configuration
+    pub fn load_tabs(&self, config: &Config, keep_hidden: bool) -> Result<TabList, Error> {
+        osutil_core::get_tabs(
+            !self.override_validation,
+            keep_hidden,
+            config.tabs_dirs(),
+            config.exclude_flags(),
+        )
+    }
+}
+
+/// Accept the codes of [`TASK_FLAGS`], optionally prefixed with `P`, in any case
+fn parse_task_flag(flag: &str) -> Result<String, String> {
+    if osutil_core::is_known_task_flag(&flag.to_ascii_uppercase()) {
+        Ok(flag.to_string())
+    } else {
+        let known: Vec<&str> = TASK_FLAGS.iter().map(|(code, _)| *code).collect();
+        Err(format!("expected one of {}", known.join(", ")))
+    }
 }
@@ -1,16 +1,20 @@
 use crate::{float::FloatContent, hint::Shortcut, shortcuts, theme};
+use osutil_core::FailurePolicy;
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind},
     layout::Alignment,
     prelude::*,
     symbols::border,
-    widgets::{Block, Clear, List},
+    widgets::{Block, Clear, List, Paragraph, Wrap},
 };
 use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
 
 pub enum ConfirmStatus {
     Confirm,
     Abort,
+    /// Show the script that would run, then come back to the prompt
+    ShowScript,
     None,
 }
 
@@ -19,10 +23,14 @@ pub struct ConfirmPrompt {
     names: Box<[String]>,
     scroll: usize,
     pub status: ConfirmStatus,
+    /// What to do when one of several entries fails
+    pub on_failure: FailurePolicy,
+    /// Requirements that cannot run. The entries cannot be confirmed while there are any.
+    unmet: Box<[String]>,
 }
 
 impl ConfirmPrompt {
-    pub fn new(names: &[&str]) -> Self {
+    pub fn new(names: &[&str], on_failure: FailurePolicy, unmet: Vec<String>) -> Self {
         let max_count_str = format!("{}", names.len());
         let names = names
             .iter()
@@ -41,9 +49,19 @@ impl ConfirmPrompt {
             names,
             scroll: 0,
             status: ConfirmStatus::None,
+            on_failure,
+            unmet: unmet.into_boxed_slice(),
         }
     }
 
+    fn can_confirm(&self) -> bool {
+        self.unmet.is_empty()
+    }
+
+    fn is_batch(&self) -> bool {
+        self.names.len() > 1
+    }
+
     pub fn scroll_down(&mut self) {
         if self.scroll + self.inner_area_height < self.names.len() - 1 {
             self.scroll += 1;
@@ -59,26 +77,70 @@ impl ConfirmPrompt {
 
 impl FloatContent for ConfirmPrompt {
     fn draw(&mut self, frame: &mut Frame, area: Rect, theme: &theme::Theme) {
+        let mut keys = vec![Span::raw(" [")];
+        if self.can_confirm() {
+            keys.extend([
+                Span::styled("y", Style::default().fg(theme.success_color())),
+                Span::raw("] to continue ["),
+            ]);
+        }
+        keys.extend([
+            Span::styled("n", Style::default().fg(theme.fail_color())),
+            Span::raw("] to abort "),
+        ]);
         let block = Block::bordered()
             .border_set(border::ROUNDED)
             .title(" Confirm selections ")
-            .title_bottom(Line::from(vec![
-                Span::raw(" ["),
-                Span::styled("y", Style::default().fg(theme.success_color())),
-                Span::raw("] to continue ["),
-                Span::styled("n", Style::default().fg(theme.fail_color())),
-                Span::raw("] to abort "),
-            ]))
+            .title_bottom(Line::from(keys))
             .title_alignment(Alignment::Center)
             .title_style(Style::default().bold())
             .style(Style::default());
 
-        let inner_area = block.inner(area);
-        self.inner_area_height = inner_area.height as usize;
+        let mut inner_area = block.inner(area);
 
         frame.render_widget(Clear, area);
         frame.render_widget(block, area);
 
+        if !self.can_confirm() {
+            // One line for the heading and one to separate the list, plus each message as wrapped
+            let width = usize::from(inner_area.width.max(1));
+            let height = self
+                .unmet
+                .iter()
+                .map(|line| line.width().div_ceil(width).max(1))
+                .sum::<usize>()
+                + 2;
+            let [unmet_area, rest] =
+                Layout::vertical([Constraint::Length(height as u16), Constraint::Min(1)])
+                    .areas(inner_area);
+            let fail = Style::default().fg(theme.fail_color());
+            let unmet: Text = std::iter::once(Line::styled("Cannot run:", fail.bold()))
+                .chain(
+                    self.unmet
+                        .iter()
+                        .map(|line| Line::styled(line.as_str(), fail)),
+                )
+                .collect();
+            frame.render_widget(Paragraph::new(unmet).wrap(Wrap { trim: false }), unmet_area);
+            inner_area = rest;
+        }
+
+        // The failure policy only matters when there is more than one entry
+        if self.is_batch() {
+            let [policy_area, list_area] =
+                Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(inner_area);
+            let policy = Line::from(vec![
+                Span::raw("On failure: "),
+                Span::styled(self.on_failure.to_string(), Style::default().bold()),
+                Span::raw(" ["),
+                Span::styled("f", Style::default().fg(theme.focused_color())),
+                Span::raw("] to change"),
+            ]);
+            frame.render_widget(policy, policy_area);
+            inner_area = list_area;
+        }
+        self.inner_area_height = inner_area.height as usize;
+
         let paths_text = self
             .names
             .iter()
@@ -109,8 +171,13 @@ impl FloatContent for ConfirmPrompt {
         use ConfirmStatus::*;
         use KeyCode::{Char, Down, Esc, Up};
         self.status = match key.code {
-            Char('y') | Char('Y') => Confirm,
+            Char('y') | Char('Y') if self.can_confirm() => Confirm,
             Char('n') | Char('N') | Esc | Char('q') => Abort,
+            Char('s') | Char('S') => ShowScript,
+            Char('f') | Char('F') if self.is_batch() => {
+                self.on_failure = self.on_failure.next();
+                None
+            }
             Char('j') | Char('J') | Down => {
                 self.scroll_down();
                 None
@@ -128,20 +195,25 @@ impl FloatContent for ConfirmPrompt {
         use ConfirmStatus::*;
         match self.status {
             Confirm | Abort => true,
-            None => false,
+            ShowScript | None => false,
         }
     }
 
     fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>) {
-        (
-            "Confirmation prompt",
-            shortcuts!(
-                ("Continue", ["Y", "y"]),
-                ("Abort", ["N", "n", "q", "Esc"]),
-                ("Scroll up", ["k", "Up"]),
-                ("Scroll down", ["j", "Down"]),
-                ("Close osutil", ["CTRL-c"]),
-            ),
+        let mut shortcuts = shortcuts!(
+            ("Abort", ["N", "n", "q", "Esc"]),
+            ("Show the script", ["s"]),
+            ("Scroll up", ["k", "Up"]),
+            ("Scroll down", ["j", "Down"]),
+            ("Close osutil", ["CTRL-c"]),
         )
+        .into_vec();
+        if self.can_confirm() {
+            shortcuts.insert(0, Shortcut::new("Continue", ["Y", "y"]));
+        }
+        if self.is_batch() {
+            shortcuts.push(Shortcut::new("Change what happens on failure", ["f"]));
+        }
+        ("Confirmation prompt", shortcuts.into_boxed_slice())
     }
 }
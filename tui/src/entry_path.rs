@@ -0,0 +1,43 @@
+use osutil_core::{ListNode, Tab, ego_tree::Tree};
+use std::rc::Rc;
+
+/// Where `node` is in `tabs`: the name of its tab, its directories and its own name
+pub fn entry_path(tabs: &[Tab], node: &Rc<ListNode>) -> Option<Vec<String>> {
+    tabs.iter().find_map(|tab| {
+        let found = tab
+            .tree
+            .root()
+            .descendants()
+            .find(|candidate| Rc::ptr_eq(candidate.value(), node))?;
+        Some(
+            tab.entry_path(found.id())
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        )
+    })
+}
+
+/// The entry at `path`, if it is still in `tabs`
+pub fn find_entry(tabs: &[Tab], path: &[String]) -> Option<Rc<ListNode>> {
+    let (tab_name, names) = path.split_first()?;
+    let tab = tabs.iter().find(|tab| tab.name == *tab_name)?;
+    let mut node = tab.tree.root();
+    for name in names {
+        node = node.children().find(|child| child.value().name == *name)?;
+    }
+    (node.parent().is_some() && !node.has_children()).then(|| node.value().clone())
+}
+
+/// A tab named `name` listing `nodes` of `tabs` in order, such as the Favorites and Recent tabs
+pub fn list_tab(name: &str, tabs: &[Tab], nodes: impl IntoIterator<Item = Rc<ListNode>>) -> Tab {
+    // Every tab shares the same root node
+    let mut tree = Tree::new(tabs[0].tree.root().value().clone());
+    for node in nodes {
+        tree.root_mut().append(node);
+    }
+    Tab {
+        name: name.to_string(),
+        tree,
+    }
+}
@@ -0,0 +1,60 @@
+use crate::entry_path::{find_entry, list_tab};
+use osutil_core::Tab;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+};
+
+/// Name of the tab listing the pinned entries
+pub const TAB_NAME: &str = "Favorites";
+const FAVORITES_FILE: &str = "favorites.json";
+
+/// The entries pinned to the Favorites tab. Each one is kept as the name of its tab, followed by
+/// the names of the directories leading to it and its own name.
+#[derive(Default)]
+pub struct Favorites {
+    paths: Vec<Vec<String>>,
+}
+
+impl Favorites {
+    /// The pinned entries saved by earlier sessions
+    pub fn load() -> io::Result<Self> {
+        let paths = match fs::read_to_string(osutil_core::state_file(FAVORITES_FILE)?) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { paths })
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = osutil_core::state_file(FAVORITES_FILE)?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, serde_json::to_string_pretty(&self.paths)?)
+    }
+
+    /// Pin the entry at `path`, or unpin it if it already is, and save the change. Returns
+    /// whether the entry is pinned now.
+    pub fn toggle(&mut self, path: Vec<String>) -> io::Result<bool> {
+        let pinned = match self.paths.iter().position(|pinned| *pinned == path) {
+            Some(index) => {
+                self.paths.remove(index);
+                false
+            }
+            None => {
+                self.paths.push(path);
+                true
+            }
+        };
+        self.save()?;
+        Ok(pinned)
+    }
+
+    /// The Favorites tab, listing the pinned entries found in `tabs` in the order they were
+    /// pinned. Entries that are not there anymore are left out, but stay pinned so that they
+    /// come back if they are available again.
+    pub fn tab(&self, tabs: &[Tab]) -> Tab {
+        let nodes = self.paths.iter().filter_map(|path| find_entry(tabs, path));
+        list_tab(TAB_NAME, tabs, nodes)
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{state::ListEntry, theme::Theme};
-use osutil_core::{Tab, ego_tree::NodeId};
+use crate::{entry_path::entry_path, state::ListEntry, theme::Theme};
+use osutil_core::{ListNode, Tab, ego_tree::NodeId};
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
     prelude::*,
@@ -14,6 +14,17 @@ pub enum SearchAction {
     Update,
 }
 
+/// Score of each character of the name matched by the search
+const MATCH_SCORE: i32 = 16;
+/// Bonus for a match at the start of a word
+const WORD_START_BONUS: i32 = 8;
+/// Further bonus when the match starts with the first character of the name
+const PREFIX_BONUS: i32 = 8;
+/// Bonus for a match right after the previous one
+const CONSECUTIVE_BONUS: i32 = 4;
+/// Penalty for each character skipped between two matches
+const GAP_PENALTY: i32 = 1;
+
 pub struct Filter {
     // Use Vec<char> to handle multi-byte characters like emojis
     search_input: Vec<char>,
@@ -48,39 +59,98 @@ impl Filter {
         self.completion = None;
     }
 
-    pub fn update_items(&mut self, tabs: &[Tab], current_tab: usize, node: NodeId) {
+    /// List the children of `node` in the current tab, or, while there is a search, the entries
+    /// of every tab matching it. The first `synthetic_tabs` tabs repeat entries of the others and
+    /// are only searched while current, keeping their own order.
+    pub fn update_items(
+        &mut self,
+        tabs: &[Tab],
+        current_tab: usize,
+        node: NodeId,
+        show_hidden: bool,
+        synthetic_tabs: usize,
+    ) {
         if self.search_input.is_empty() {
             let curr = tabs[current_tab].tree.get(node).unwrap();
 
             self.items = curr
                 .children()
+                .filter(|node| show_hidden || node.value().hidden.is_none())
                 .map(|node| ListEntry {
                     node: node.value().clone(),
                     id: node.id(),
                     has_children: node.has_children(),
+                    matched: Vec::new(),
+                    location: Vec::new(),
+                })
+                .collect();
+        } else if current_tab < synthetic_tabs {
+            let query = Query::parse(&self.search_input.iter().collect::<String>());
+            let real_tabs = &tabs[synthetic_tabs..];
+            self.items = tabs[current_tab]
+                .tree
+                .root()
+                .children()
+                .filter(|node| show_hidden || node.value().hidden.is_none())
+                .filter(|node| query.matches_flags(node.value()))
+                .filter_map(|node| {
+                    let mut location = entry_path(real_tabs, node.value())?;
+                    location.pop();
+                    if !query.matches_tab(&location[0]) {
+                        return None;
+                    }
+                    let (_, matched) = query.rank(node.value())?;
+                    Some(ListEntry {
+                        node: node.value().clone(),
+                        id: node.id(),
+                        has_children: false,
+                        matched,
+                        location,
+                    })
                 })
                 .collect();
         } else {
-            self.items.clear();
-            let query_lower = self.search_input.iter().collect::<String>().to_lowercase();
-            for tab in tabs {
+            let query = Query::parse(&self.search_input.iter().collect::<String>());
+            let mut found: Vec<(Rank, ListEntry)> = Vec::new();
+            for tab in tabs.iter().skip(synthetic_tabs) {
+                if !query.matches_tab(&tab.name) {
+                    continue;
+                }
                 let mut stack = vec![tab.tree.root().id()];
                 while let Some(node_id) = stack.pop() {
                     let node = tab.tree.get(node_id).unwrap();
-                    if node.value().name.to_lowercase().contains(&query_lower)
-                        && !node.has_children()
-                    {
-                        self.items.push(ListEntry {
+                    if !show_hidden && node.value().hidden.is_some() {
+                        continue;
+                    }
+                    stack.extend(node.children().map(|child| child.id()));
+                    if node.has_children() || !query.matches_flags(node.value()) {
+                        continue;
+                    }
+                    let Some((rank, matched)) = query.rank(node.value()) else {
+                        continue;
+                    };
+                    let mut location = tab.entry_path(node_id);
+                    location.pop();
+                    let location = location.into_iter().map(String::from).collect();
+                    found.push((
+                        rank,
+                        ListEntry {
                             node: node.value().clone(),
                             id: node.id(),
                             has_children: false,
-                        });
-                    }
-                    stack.extend(node.children().map(|child| child.id()));
+                            matched,
+                            location,
+                        },
+                    ));
                 }
             }
-            self.items
-                .sort_unstable_by(|a, b| a.node.name.cmp(&b.node.name));
+            found.sort_by(|(a_rank, a), (b_rank, b)| {
+                b_rank
+                    .cmp(a_rank)
+                    .then_with(|| a.node.name.cmp(&b.node.name))
+                    .then_with(|| a.location.cmp(&b.location))
+            });
+            self.items = found.into_iter().map(|(_, item)| item).collect();
         }
         self.update_completion();
     }
@@ -253,3 +323,439 @@ impl Filter {
         self.input_position = 0;
     }
 }
+
+/// How well an entry matches a search: entries whose name matches come before those matching
+/// only by their description or task flags, then the higher score first
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Rank {
+    in_name: bool,
+    score: i32,
+}
+
+/// A search, split into its operators and the text left, in lowercase:
+///
+/// - `tab:NAME` keeps the entries of tabs whose name contains NAME
+/// - `flag:CODE` keeps the entries with the task flag CODE
+/// - `-flag:CODE` leaves out the entries with the task flag CODE
+///
+/// Double quotes keep spaces in a term, as in `tab:"applications setup"`.
+struct Query {
+    text: Vec<char>,
+    tabs: Vec<String>,
+    flags: Vec<String>,
+    excluded_flags: Vec<String>,
+}
+
+impl Query {
+    fn parse(input: &str) -> Self {
+        let mut query = Query {
+            text: Vec::new(),
+            tabs: Vec::new(),
+            flags: Vec::new(),
+            excluded_flags: Vec::new(),
+        };
+        let mut words = Vec::new();
+        for word in split_terms(input) {
+            let lower = word.to_lowercase();
+            match lower.split_once(':') {
+                // Operators are ignored until they have a value
+                Some(("tab" | "flag" | "-flag", "")) => {}
+                Some(("tab", name)) => query.tabs.push(name.to_string()),
+                Some(("flag", flag)) => query.flags.push(flag.to_string()),
+                Some(("-flag", flag)) => query.excluded_flags.push(flag.to_string()),
+                _ => words.push(lower),
+            }
+        }
+        query.text = words.join(" ").chars().collect();
+        query
+    }
+
+    fn matches_tab(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.tabs.is_empty() || self.tabs.iter().any(|tab| name.contains(tab.as_str()))
+    }
+
+    fn matches_flags(&self, node: &ListNode) -> bool {
+        self.flags.iter().all(|flag| node.has_task_flag(flag))
+            && !self
+                .excluded_flags
+                .iter()
+                .any(|flag| node.has_task_flag(flag))
+    }
+
+    /// How well the text matches the entry, with the positions of the characters of the name it
+    /// matched. Without text, every entry matches.
+    fn rank(&self, node: &ListNode) -> Option<(Rank, Vec<usize>)> {
+        if self.text.is_empty() {
+            return Some((
+                Rank {
+                    in_name: true,
+                    score: 0,
+                },
+                Vec::new(),
+            ));
+        }
+        if let Some((score, matched)) = fuzzy_match(&self.text, &node.name) {
+            return Some((
+                Rank {
+                    in_name: true,
+                    score,
+                },
+                matched,
+            ));
+        }
+        let text: String = self.text.iter().collect();
+        let found = node.description.to_lowercase().contains(&text)
+            || node.task_list.to_lowercase().contains(&text);
+        found.then(|| {
+            (
+                Rank {
+                    in_name: false,
+                    score: 0,
+                },
+                Vec::new(),
+            )
+        })
+    }
+}
+
+/// Split `input` at whitespace outside of double quotes, dropping the quotes and empty terms. A
+/// quote left open runs to the end.
+fn split_terms(input: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            c => term.push(c),
+        }
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+    terms
+}
+
+/// Match the characters of `query`, in lowercase, in order anywhere in `name`. Returns the score
+/// of the best match, higher for matches at word starts, most of all at the start of the name,
+/// and for runs of consecutive characters, lower for characters skipped in between, along with
+/// the positions of the matched characters.
+fn fuzzy_match(query: &[char], name: &str) -> Option<(i32, Vec<usize>)> {
+    let name: Vec<char> = name.chars().collect();
+    let lower: Vec<char> = name
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let word_start = |index: usize| {
+        index == 0 || {
+            let (previous, current) = (name[index - 1], name[index]);
+            !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+        }
+    };
+
+    // best[i][j]: score of the best match of query[..=i] with query[i] matched at name[j], and
+    // where query[i - 1] was matched
+    let mut best: Vec<Vec<Option<(i32, usize)>>> = vec![vec![None; name.len()]; query.len()];
+    for (i, query_char) in query.iter().enumerate() {
+        for j in (i..name.len()).filter(|&j| lower[j] == *query_char) {
+            let bonus = MATCH_SCORE + if word_start(j) { WORD_START_BONUS } else { 0 };
+            best[i][j] = if i == 0 {
+                Some((bonus + if j == 0 { PREFIX_BONUS } else { 0 }, 0))
+            } else {
+                (i - 1..j)
+                    .filter_map(|k| {
+                        let (score, _) = best[i - 1][k]?;
+                        let link = if k + 1 == j {
+                            CONSECUTIVE_BONUS
+                        } else {
+                            -GAP_PENALTY * (j - k - 1) as i32
+                        };
+                        Some((score + link + bonus, k))
+                    })
+                    .max_by_key(|(score, _)| *score)
+            };
+        }
+    }
+
+    let last = query.len().checked_sub(1)?;
+    let (mut position, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, cell)| Some((j, cell.as_ref()?.0)))
+        .max_by_key(|(_, score)| *score)?;
+    let mut matched = vec![position; query.len()];
+    for i in (1..query.len()).rev() {
+        position = best[i][position].unwrap().1;
+        matched[i - 1] = position;
+    }
+    Some((score, matched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filter, Query, Rank, fuzzy_match};
+    use osutil_core::{Command, ListNode, Tab, ego_tree::Tree};
+    use std::rc::Rc;
+
+    fn entry(name: &str, description: &str, task_list: &str) -> ListNode {
+        ListNode {
+            name: name.to_string(),
+            description: description.to_string(),
+            command: Command::Raw(String::new()),
+            task_list: task_list.to_string(),
+            multi_select: true,
+            hidden: None,
+            requires: Vec::new(),
+            after: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    fn tab(name: &str, entries: Vec<ListNode>) -> Tab {
+        let mut tree = Tree::new(Rc::new(ListNode {
+            command: Command::None,
+            ..entry("root", "", "")
+        }));
+        for entry in entries {
+            tree.root_mut().append(Rc::new(entry));
+        }
+        Tab {
+            name: name.to_string(),
+            tree,
+        }
+    }
+
+    /// The results of searching `text` from the first tab, as `Tab › Entry`
+    fn search(tabs: &[Tab], text: &str) -> Vec<String> {
+        let mut filter = Filter::new();
+        filter.search_input = text.chars().collect();
+        filter.update_items(tabs, 0, tabs[0].tree.root().id(), false, 0);
+        filter
+            .item_list()
+            .iter()
+            .map(|item| format!("{} › {}", item.location.join(" › "), item.node.name))
+            .collect()
+    }
+
+    fn score(query: &str, name: &str) -> Option<i32> {
+        let query: Vec<char> = query.chars().collect();
+        fuzzy_match(&query, name).map(|(score, _)| score)
+    }
+
+    fn positions(query: &str, name: &str) -> Option<Vec<usize>> {
+        let query: Vec<char> = query.chars().collect();
+        fuzzy_match(&query, name).map(|(_, matched)| matched)
+    }
+
+    #[test]
+    fn letters_must_appear_in_order() {
+        assert!(score("dckr", "Docker").is_some());
+        assert!(score("sysupd", "System Update").is_some());
+        assert_eq!(score("rd", "Docker"), None);
+        assert_eq!(score("dockers", "Docker"), None);
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        assert_eq!(score("docker", "DOCKER"), score("docker", "docker"));
+    }
+
+    #[test]
+    fn prefix_beats_word_start_beats_scattered() {
+        let prefix = score("doc", "Docker").unwrap();
+        let word_start = score("doc", "Install Docker").unwrap();
+        let scattered = score("doc", "Radio Cache").unwrap();
+        assert!(prefix > word_start, "{prefix} <= {word_start}");
+        assert!(word_start > scattered, "{word_start} <= {scattered}");
+    }
+
+    #[test]
+    fn consecutive_letters_beat_gaps() {
+        assert!(score("ab", "ab").unwrap() > score("ab", "axxb").unwrap());
+        assert!(score("ab", "axb").unwrap() > score("ab", "axxb").unwrap());
+    }
+
+    #[test]
+    fn positions_of_the_best_match_are_returned() {
+        assert_eq!(positions("doc", "Docker"), Some(vec![0, 1, 2]));
+        assert_eq!(positions("doc", "Install Docker"), Some(vec![8, 9, 10]));
+        assert_eq!(positions("gs", "Git Setup"), Some(vec![0, 4]));
+        // A later word start is preferred to the first occurrence
+        assert_eq!(positions("s", "Mass Storage"), Some(vec![5]));
+        assert_eq!(positions("nv", "NeoVim"), Some(vec![0, 3]));
+    }
+
+    #[test]
+    fn positions_count_characters_not_bytes() {
+        assert_eq!(positions("ab", "éa b"), Some(vec![1, 3]));
+    }
+
+    #[test]
+    fn name_matches_rank_above_description_matches() {
+        let in_name = Rank {
+            in_name: true,
+            score: 0,
+        };
+        let in_description = Rank {
+            in_name: false,
+            score: 100,
+        };
+        assert!(in_name > in_description);
+        assert!(
+            Rank {
+                in_name: true,
+                score: 2
+            } > Rank {
+                in_name: true,
+                score: 1
+            }
+        );
+    }
+
+    #[test]
+    fn results_are_ordered_by_rank() {
+        let tabs = [tab(
+            "Tab",
+            vec![
+                entry("Radio Cache", "", ""),
+                entry("Mentions docs", "Documentation", ""),
+                entry("Install Docker", "", ""),
+                entry("Docker", "", ""),
+            ],
+        )];
+        assert_eq!(
+            search(&tabs, "doc"),
+            [
+                "Tab › Docker",
+                "Tab › Install Docker",
+                "Tab › Mentions docs",
+                "Tab › Radio Cache",
+            ]
+        );
+        let tabs = [tab(
+            "Tab",
+            vec![
+                entry("Zsh", "A docker shell", ""),
+                entry("Radio Cache", "", ""),
+            ],
+        )];
+        assert_eq!(search(&tabs, "doc"), ["Tab › Radio Cache", "Tab › Zsh"]);
+    }
+
+    #[test]
+    fn ties_are_broken_by_name_then_location() {
+        let tabs = [
+            tab("Beta", vec![entry("Git Setup", "", "")]),
+            tab(
+                "Alpha",
+                vec![entry("Git Setup", "", ""), entry("Fit Setup", "", "")],
+            ),
+        ];
+        assert_eq!(
+            search(&tabs, "setup"),
+            ["Alpha › Fit Setup", "Alpha › Git Setup", "Beta › Git Setup"]
+        );
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn text(query: &Query) -> String {
+        query.text.iter().collect()
+    }
+
+    #[test]
+    fn plain_words_are_the_text() {
+        let query = Query::parse("  Docker   Setup ");
+        assert_eq!(text(&query), "docker setup");
+        assert!(query.tabs.is_empty());
+        assert!(query.flags.is_empty());
+        assert!(query.excluded_flags.is_empty());
+    }
+
+    #[test]
+    fn operators_are_taken_out_of_the_text() {
+        let query = Query::parse("tab:Security firewall flag:SS -flag:D");
+        assert_eq!(text(&query), "firewall");
+        assert_eq!(query.tabs, strings(&["security"]));
+        assert_eq!(query.flags, strings(&["ss"]));
+        assert_eq!(query.excluded_flags, strings(&["d"]));
+    }
+
+    #[test]
+    fn operators_can_repeat() {
+        let query = Query::parse("flag:I flag:SS -flag:D -flag:K tab:a tab:b");
+        assert_eq!(query.flags, strings(&["i", "ss"]));
+        assert_eq!(query.excluded_flags, strings(&["d", "k"]));
+        assert_eq!(query.tabs, strings(&["a", "b"]));
+    }
+
+    #[test]
+    fn operators_without_a_value_are_ignored() {
+        let query = Query::parse("tab: flag: -flag: git");
+        assert_eq!(text(&query), "git");
+        assert!(query.tabs.is_empty());
+        assert!(query.flags.is_empty());
+        assert!(query.excluded_flags.is_empty());
+    }
+
+    #[test]
+    fn unknown_prefixes_are_text() {
+        let query = Query::parse("http://example tag:x");
+        assert_eq!(text(&query), "http://example tag:x");
+        assert!(query.tabs.is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_spaces_in_a_term() {
+        let query = Query::parse(r#"tab:"Applications Setup" "git  setup""#);
+        assert_eq!(query.tabs, strings(&["applications setup"]));
+        assert_eq!(text(&query), "git  setup");
+        let query = Query::parse(r#""tab:Desktop Environments""#);
+        assert_eq!(query.tabs, strings(&["desktop environments"]));
+    }
+
+    #[test]
+    fn unclosed_and_empty_quotes() {
+        let query = Query::parse(r#"tab:"system set"#);
+        assert_eq!(query.tabs, strings(&["system set"]));
+        let query = Query::parse(r#"tab:"" "" docker"#);
+        assert!(query.tabs.is_empty());
+        assert_eq!(text(&query), "docker");
+    }
+
+    #[test]
+    fn tab_operator_matches_part_of_the_name() {
+        let query = Query::parse(r#"tab:"ions set""#);
+        assert!(query.matches_tab("Applications Setup"));
+        assert!(!query.matches_tab("System Setup"));
+        assert!(Query::parse("git").matches_tab("Anything"));
+    }
+
+    #[test]
+    fn flag_operators_filter_by_task_flag() {
+        let node = entry("Entry", "", "I SS");
+        assert!(Query::parse("flag:ss").matches_flags(&node));
+        assert!(!Query::parse("flag:ss flag:d").matches_flags(&node));
+        assert!(!Query::parse("-flag:i").matches_flags(&node));
+        assert!(Query::parse("-flag:d").matches_flags(&node));
+    }
+
+    #[test]
+    fn descriptions_and_task_flags_are_searched() {
+        let tabs = [
+            tab("Security", vec![entry("Firewall", "Set up UFW", "SS")]),
+            tab("System", vec![entry("Kernel", "Pick a kernel", "K")]),
+        ];
+        assert_eq!(search(&tabs, "ufw"), ["Security › Firewall"]);
+        assert_eq!(search(&tabs, "flag:k"), ["System › Kernel"]);
+        assert_eq!(search(&tabs, "tab:sec -flag:ss"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{float::FloatContent, hint::Shortcut, shortcuts, theme::Theme};
+use osutil_core::TASK_FLAGS;
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    prelude::*,
+    symbols::border,
+    widgets::{Block, Clear, List, ListState},
+};
+
+/// Choose the task flags whose entries are hidden
+pub struct FlagFilter {
+    /// The excluded flags, in the order they were chosen
+    pub excluded: Vec<String>,
+    /// Flags excluded by the config file or the command line, which cannot be shown again here
+    locked: Vec<String>,
+    selection: ListState,
+}
+
+impl FlagFilter {
+    pub fn new(excluded: &[String], locked: &[String]) -> Self {
+        Self {
+            excluded: excluded.to_vec(),
+            locked: locked.to_vec(),
+            selection: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    fn is_excluded(&self, flag: &str) -> bool {
+        self.excluded
+            .iter()
+            .chain(&self.locked)
+            .any(|excluded| excluded.eq_ignore_ascii_case(flag))
+    }
+
+    fn is_locked(&self, flag: &str) -> bool {
+        self.locked
+            .iter()
+            .any(|locked| locked.eq_ignore_ascii_case(flag))
+    }
+
+    fn toggle_selected(&mut self) {
+        let Some((flag, _)) = self.selection.selected().and_then(|i| TASK_FLAGS.get(i)) else {
+            return;
+        };
+        if self.is_locked(flag) {
+            return;
+        }
+        if self.is_excluded(flag) {
+            self.excluded
+                .retain(|excluded| !excluded.eq_ignore_ascii_case(flag));
+        } else {
+            self.excluded.push(flag.to_string());
+        }
+    }
+
+    fn select_next(&mut self) {
+        let next = self.selection.selected().map_or(0, |index| index + 1);
+        self.selection.select(Some(next.min(TASK_FLAGS.len() - 1)));
+    }
+}
+
+impl FloatContent for FlagFilter {
+    fn draw(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" Excluded task flags ")
+            .title_bottom(Line::from(vec![
+                Span::raw(" ["),
+                Span::styled("Space", Style::default().fg(theme.focused_color())),
+                Span::raw("] to toggle ["),
+                Span::styled("Esc", Style::default().fg(theme.fail_color())),
+                Span::raw("] to close "),
+            ]))
+            .title_alignment(Alignment::Center)
+            .title_style(Style::default().bold());
+
+        let items = TASK_FLAGS.iter().map(|(flag, description)| {
+            if !self.is_excluded(flag) {
+                return Line::from(format!("[ ] {flag:<2} {description}"));
+            }
+            let mut line = Line::from(format!("[x] {flag:<2} {description}"));
+            if self.is_locked(flag) {
+                line.push_span(Span::raw(" (set by the configuration)").dim());
+            }
+            line.style(Style::default().fg(theme.fail_color()))
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed());
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut self.selection);
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent) -> bool {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.select_next(),
+            MouseEventKind::ScrollUp => self.selection.select_previous(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Returns true to close the window
+    fn handle_key_event(&mut self, key: &KeyEvent) -> bool {
+        use KeyCode::{Char, Down, Enter, Esc, Up};
+        match key.code {
+            Char('j') | Down => self.select_next(),
+            Char('k') | Up => self.selection.select_previous(),
+            Char(' ') | Enter => self.toggle_selected(),
+            Char('q') | Char('x') | Esc => return true,
+            _ => {}
+        }
+        false
+    }
+
+    // Closing is handled in `handle_key_event`
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>) {
+        (
+            "Task flags",
+            shortcuts!(
+                ("Hide or show entries with this flag", ["Space", "Enter"]),
+                ("Select item above", ["k", "Up"]),
+                ("Select item below", ["j", "Down"]),
+                ("Close window", ["q", "x", "Esc"]),
+            ),
+        )
+    }
+}
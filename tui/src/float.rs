@@ -11,6 +11,11 @@ pub trait FloatContent {
     fn handle_mouse_event(&mut self, key: &MouseEvent) -> bool;
     fn is_finished(&self) -> bool;
     fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>);
+
+    /// A window to show once this one is closed
+    fn followup(&mut self) -> Option<Box<dyn FloatContent>> {
+        None
+    }
 }
 
 pub struct Float<Content: FloatContent + ?Sized> {
@@ -62,6 +62,11 @@ impl<'a> FloatingText<'a> {
         }
         .unwrap();
 
+        Self::from_script(src, title, wrap_words)
+    }
+
+    /// Show a shell script, highlighted when syntax highlighting is enabled
+    pub fn from_script(src: String, title: &str, wrap_words: bool) -> Self {
         // Apply bash syntax highlighting
         let processed_text = Self::get_highlighted_string(&src).unwrap_or_else(|| Text::from(src));
 
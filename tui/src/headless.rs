@@ -0,0 +1,623 @@
+use crate::{
+    cli::{Args, Commands, ConfigAction},
+    history::{self, PendingRun},
+    running_command::{assemble_script, secret_names},
+};
+use nix::sys::termios::{self, LocalFlags, SetArg};
+use osutil_core::{
+    Command, EntrySettings, FailurePolicy, HiddenReason, Input, InputKind, ListNode, TabList,
+};
+use serde::Serialize;
+use std::{
+    env,
+    io::{self, ErrorKind, IsTerminal, Write},
+    os::unix::process::ExitStatusExt,
+    path::Path,
+    process::{self, ExitStatus},
+    rc::Rc,
+};
+
+/// A subcommand that works on the loaded tabs
+enum TabCommand<'a> {
+    Run(&'a [String]),
+    Rerun(Option<u64>),
+    List { json: bool, all: bool },
+    Explain(&'a str),
+}
+
+/// Run a subcommand without entering the TUI. Returns the process exit code.
+pub fn run(args: &Args, command: &Commands) -> i32 {
+    let command = match command {
+        Commands::Validate { path } => return write_status(validate(path.as_deref())),
+        Commands::History { id } => return write_status(show_history(*id)),
+        Commands::Config {
+            action: ConfigAction::Show,
+        } => return write_status(show_config(args)),
+        Commands::Run { names } => TabCommand::Run(names),
+        Commands::Rerun { id } => TabCommand::Rerun(*id),
+        Commands::List { json, all } => TabCommand::List {
+            json: *json,
+            all: *all,
+        },
+        Commands::Explain { name } => TabCommand::Explain(name),
+    };
+
+    let (config, errors) = args.read_config();
+    for error in &errors {
+        eprintln!("osutil: {error}");
+    }
+    if !errors.is_empty() {
+        return 1;
+    }
+
+    // Entries to run are looked up with hidden ones kept, to tell why they are unavailable
+    let keep_hidden = match command {
+        TabCommand::List { all, .. } => all,
+        TabCommand::Run(_) | TabCommand::Rerun(_) | TabCommand::Explain(_) => true,
+    };
+    let tabs = match args.load_tabs(&config, keep_hidden) {
+        Ok(tabs) => tabs,
+        Err(e) => {
+            eprintln!("osutil: {e}");
+            return 1;
+        }
+    };
+    for error in tabs.errors() {
+        match error {
+            osutil_core::Error::DependencyCycle { .. } | osutil_core::Error::Invalid { .. } => {
+                eprintln!("osutil: {error}")
+            }
+            _ => eprintln!("osutil: skipped tab: {error}"),
+        }
+    }
+
+    if let TabCommand::Run(_) | TabCommand::Rerun(_) = command {
+        for unused in config.unused_entries(&tabs) {
+            eprintln!("osutil: {unused}");
+        }
+    }
+
+    let options = RunOptions {
+        on_failure: config.on_failure(),
+        settings: config.entries().clone(),
+        dry_run: args.dry_run,
+    };
+    match command {
+        TabCommand::Run(names) => run_entries(&tabs, names, &options),
+        TabCommand::Rerun(id) => rerun(&tabs, id, &options),
+        TabCommand::List { json, .. } => write_status(list_entries(&tabs, json)),
+        TabCommand::Explain(name) => write_status(explain_entry(&tabs, name)),
+    }
+}
+
+/// Print the effective configuration. Files that could not be read are reported, and make the
+/// exit code non-zero. The tabs are loaded to tell which entry variables hold passwords, and
+/// which `[entries]` tables name no script entry.
+fn show_config(args: &Args) -> io::Result<i32> {
+    let (config, errors) = args.read_config();
+    for error in &errors {
+        eprintln!("osutil: {error}");
+    }
+    let tabs = match args.load_tabs(&config, true) {
+        Ok(tabs) => Some(tabs),
+        Err(e) => {
+            eprintln!("osutil: {e}; hiding every entry variable");
+            None
+        }
+    };
+    for unused in tabs.iter().flat_map(|tabs| config.unused_entries(tabs)) {
+        eprintln!("osutil: {unused}");
+    }
+    write!(io::stdout().lock(), "{}", config.show(tabs.as_ref()))?;
+    Ok(i32::from(!errors.is_empty()))
+}
+
+fn validate(path: Option<&Path>) -> io::Result<i32> {
+    let issues = match osutil_core::validate_tabs(path) {
+        Ok(issues) => issues,
+        Err(e) => {
+            eprintln!("osutil: {e}");
+            return Ok(1);
+        }
+    };
+    let mut out = io::stdout().lock();
+    for issue in &issues {
+        writeln!(out, "{issue}")?;
+    }
+    if issues.is_empty() {
+        eprintln!("No problems found");
+        Ok(0)
+    } else {
+        eprintln!("{} problem(s) found", issues.len());
+        Ok(1)
+    }
+}
+
+#[derive(Serialize)]
+struct EntryInfo<'a> {
+    tab: &'a str,
+    path: Vec<&'a str>,
+    name: &'a str,
+    description: &'a str,
+    kind: &'static str,
+    task_list: &'a str,
+    multi_select: bool,
+    hidden: Option<&'a HiddenReason>,
+    requires: &'a [String],
+    after: &'a [String],
+}
+
+fn list_entries(tabs: &TabList, json: bool) -> io::Result<i32> {
+    let entries: Vec<EntryInfo> = tabs
+        .iter()
+        .flat_map(|tab| {
+            tab.tree.root().descendants().skip(1).map(|node| {
+                let value = node.value();
+                let path = tab.entry_path(node.id());
+                EntryInfo {
+                    tab: &tab.name,
+                    // Only the directories, between the tab and the entry
+                    path: path[1..path.len() - 1].to_vec(),
+                    name: &value.name,
+                    description: &value.description,
+                    kind: command_kind(&value.command),
+                    task_list: &value.task_list,
+                    multi_select: value.multi_select,
+                    hidden: value.hidden.as_ref(),
+                    requires: &value.requires,
+                    after: &value.after,
+                }
+            })
+        })
+        .collect();
+
+    let mut out = io::stdout().lock();
+    if json {
+        match serde_json::to_string_pretty(&entries) {
+            Ok(json) => writeln!(out, "{json}")?,
+            Err(e) => {
+                eprintln!("osutil: failed to serialize entries: {e}");
+                return Ok(1);
+            }
+        }
+        return Ok(0);
+    }
+
+    let mut current_tab = None;
+    for entry in &entries {
+        if current_tab != Some(entry.tab) {
+            writeln!(out, "{}", entry.tab)?;
+            current_tab = Some(entry.tab);
+        }
+        let indent = "  ".repeat(entry.path.len() + 1);
+        let mut line = if entry.kind == "directory" {
+            format!("{indent}{}/", entry.name)
+        } else {
+            let mut details = vec![entry.kind];
+            if !entry.task_list.is_empty() {
+                details.push(entry.task_list);
+            }
+            if !entry.multi_select {
+                details.push("single-select");
+            }
+            format!("{indent}{}  ({})", entry.name, details.join(", "))
+        };
+        if let Some(reason) = entry.hidden {
+            line.push_str(&format!("  [hidden: {reason}]"));
+        }
+        writeln!(out, "{line}")?;
+        if entry.kind != "directory" {
+            for description_line in entry.description.lines() {
+                writeln!(out, "{indent}    {description_line}")?;
+            }
+        }
+    }
+    Ok(0)
+}
+
+fn explain_entry(tabs: &TabList, name: &str) -> io::Result<i32> {
+    let mut out = io::stdout().lock();
+    let mut found = false;
+    let mut available = false;
+    for tab in tabs.iter() {
+        for node in tab.tree.root().descendants().skip(1) {
+            let value = node.value();
+            if value.name != name || node.has_children() {
+                continue;
+            }
+            found = true;
+            let path = tab.entry_path(node.id()).join(" › ");
+
+            match &value.hidden {
+                None => {
+                    available = true;
+                    writeln!(out, "{path}: available")?;
+                }
+                Some(HiddenReason::NoSupportedEntries) => {
+                    writeln!(out, "{path}: hidden, no supported entries")?;
+                }
+                Some(HiddenReason::ExcludedFlag { flag }) => {
+                    writeln!(out, "{path}: hidden, the task flag {flag} is excluded")?;
+                }
+                Some(HiddenReason::Precondition(failure)) => {
+                    writeln!(out, "{path}: hidden")?;
+                    writeln!(
+                        out,
+                        "  declared on: {} (precondition #{})",
+                        failure.entry,
+                        failure.index + 1
+                    )?;
+                    writeln!(out, "  checks:      {}", failure.data)?;
+                    writeln!(out, "  expected:    {}", failure.expected)?;
+                    writeln!(out, "  observed:    {}", failure.observed)?;
+                }
+            }
+        }
+    }
+
+    if !found {
+        eprintln!("osutil: no such entry: {name}");
+    }
+    Ok(if available { 0 } else { 1 })
+}
+
+fn command_kind(command: &Command) -> &'static str {
+    match command {
+        Command::Raw(_) => "command",
+        Command::LocalFile { .. } => "script",
+        Command::None => "directory",
+    }
+}
+
+/// How `run` and `rerun` run the entries
+struct RunOptions {
+    on_failure: FailurePolicy,
+    /// Settings from the config file applied to the entries
+    settings: EntrySettings,
+    /// Only print the script that would run
+    dry_run: bool,
+}
+
+/// Run the named entries and their prerequisites, or only print their script on a dry run
+fn run_entries(tabs: &TabList, names: &[String], options: &RunOptions) -> i32 {
+    let (selected, errors) = tabs.resolve_entries(names);
+    for error in &errors {
+        eprintln!("osutil: {error}");
+    }
+    if !errors.is_empty() {
+        return 1;
+    }
+
+    let (nodes, unmet) = tabs.with_prerequisites(&selected);
+    for requirement in &unmet {
+        eprintln!("osutil: {requirement}");
+    }
+    if !unmet.is_empty() {
+        return 1;
+    }
+    for node in nodes.iter().filter(|node| !selected.contains(node)) {
+        eprintln!("osutil: also running {}, which is required", node.name);
+    }
+    let nodes = options.settings.apply(&nodes);
+
+    let commands: Vec<&Command> = nodes.iter().map(|node| &node.command).collect();
+    let names: Vec<String> = nodes.iter().map(|node| node.name.clone()).collect();
+    let secrets = secret_names(&nodes);
+    if options.dry_run {
+        let script = assemble_script(&commands, &names, &secrets);
+        return write_status(write!(io::stdout().lock(), "{script}").map(|()| 0));
+    }
+    let nodes = match resolve_inputs(&nodes) {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            eprintln!("osutil: {e}");
+            return 1;
+        }
+    };
+    let pending_run = PendingRun::start(names, &commands, &secrets);
+    let failure = execute_entries(&nodes, options.on_failure);
+    let code = failure.as_ref().map_or(0, |failure| failure.code);
+    let signal = failure.and_then(|failure| failure.signal);
+    // Output goes straight to the terminal, so only the outcome is recorded. Codes are never
+    // negative, signals being mapped to 128 + signal
+    let recorded_code = u32::try_from(code).unwrap_or(1);
+    if let Err(e) = pending_run.finish(recorded_code, signal, None) {
+        eprintln!("osutil: failed to record the run in the history: {e}");
+    }
+    code
+}
+
+/// The first entry of a batch that failed
+struct Failure {
+    /// Shell-style exit code, see [`exit_code`]
+    code: i32,
+    /// Set when the entry was killed by a signal
+    signal: Option<String>,
+}
+
+fn rerun(tabs: &TabList, id: Option<u64>, options: &RunOptions) -> i32 {
+    let run = match history::find_run(id) {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            match id {
+                Some(id) => eprintln!("osutil: no such run: {id}"),
+                None => eprintln!("osutil: no runs recorded yet"),
+            }
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("osutil: failed to read the history: {e}");
+            return 1;
+        }
+    };
+    eprintln!("Re-running run #{}", run.id);
+    run_entries(tabs, &run.entries, options)
+}
+
+/// Execute the entries in order, returning the first one that failed, if any. A summary of
+/// every entry's outcome is printed for batches.
+fn execute_entries(nodes: &[Rc<ListNode>], on_failure: FailurePolicy) -> Option<Failure> {
+    let mut outcomes: Vec<String> = Vec::with_capacity(nodes.len());
+    let mut first_failure = None;
+    for (index, node) in nodes.iter().enumerate() {
+        eprintln!("==> {}", node.name);
+        let outcome = match execute(&node.command) {
+            Ok(status) if status.success() => {
+                outcomes.push("succeeded".to_string());
+                continue;
+            }
+            Ok(status) => {
+                eprintln!("osutil: {} failed ({status})", node.name);
+                let failure = Failure {
+                    code: exit_code(status),
+                    signal: status.signal().map(|signal| format!("signal {signal}")),
+                };
+                (format!("failed ({status})"), failure)
+            }
+            Err(e) => {
+                eprintln!("osutil: failed to start {}: {e}", node.name);
+                let failure = Failure {
+                    code: 1,
+                    signal: None,
+                };
+                (format!("failed to start: {e}"), failure)
+            }
+        };
+        outcomes.push(outcome.0);
+        first_failure.get_or_insert(outcome.1);
+
+        let is_last = index + 1 == nodes.len();
+        let go_on = match on_failure {
+            FailurePolicy::Stop => false,
+            FailurePolicy::Continue => true,
+            FailurePolicy::Prompt => !is_last && ask_to_continue(),
+        };
+        if !go_on {
+            break;
+        }
+    }
+
+    if nodes.len() > 1 {
+        eprintln!();
+        let width = nodes.iter().map(|node| node.name.len()).max().unwrap_or(0);
+        for (index, node) in nodes.iter().enumerate() {
+            let outcome = outcomes.get(index).map_or("skipped", String::as_str);
+            eprintln!("{:<width$}  {outcome}", node.name);
+        }
+    }
+    first_failure
+}
+
+/// Give every input of the entries a value. Inputs set in the environment are passed on as they
+/// are; the others are asked for on the terminal, or take their default without one.
+fn resolve_inputs(nodes: &[Rc<ListNode>]) -> Result<Vec<Rc<ListNode>>, String> {
+    let interactive = std::io::stdin().is_terminal();
+    let mut resolved = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let mut values = Vec::new();
+        for input in node
+            .unanswered_inputs()
+            .filter(|input| env::var_os(&input.name).is_none())
+        {
+            let value = if interactive {
+                ask_input(input).map_err(|e| format!("failed to read {}: {e}", input.name))?
+            } else if let Some(default) = &input.default {
+                default.clone()
+            } else {
+                return Err(format!(
+                    "{} needs a value for {}; set it in the environment",
+                    node.name, input.name
+                ));
+            };
+            values.push((input.name.clone(), value));
+        }
+        resolved.push(Rc::new(ListNode {
+            command: node.command.with_env(values),
+            ..ListNode::clone(node)
+        }));
+    }
+    Ok(resolved)
+}
+
+/// Ask for an input on the terminal until a valid value is given. An empty answer takes the
+/// default, if there is one.
+fn ask_input(input: &Input) -> std::io::Result<String> {
+    let default = input.default.as_deref();
+    let hint = match input.kind {
+        InputKind::Text | InputKind::Password => default.map(|d| format!(" [{d}]")),
+        InputKind::Choice => Some(match default {
+            Some(d) => format!(" ({}) [{d}]", input.choices.join("/")),
+            None => format!(" ({})", input.choices.join("/")),
+        }),
+        InputKind::Bool => Some(match default {
+            Some("true") => " [Y/n]".to_string(),
+            Some("false") => " [y/N]".to_string(),
+            _ => " [y/n]".to_string(),
+        }),
+    };
+    loop {
+        eprint!("{}{}: ", input.prompt, hint.as_deref().unwrap_or_default());
+        let answer = if input.kind == InputKind::Password {
+            read_hidden_line()?
+        } else {
+            let mut answer = String::new();
+            line_or_eof(std::io::stdin().read_line(&mut answer), answer)?
+        };
+        let answer = answer.trim_end_matches(['\r', '\n']);
+        let value = match (answer, default) {
+            ("", Some(default)) => default.to_string(),
+            (answer, _) => answer.to_string(),
+        };
+        match input.kind {
+            InputKind::Text | InputKind::Password => return Ok(value),
+            InputKind::Choice if input.choices.contains(&value) => return Ok(value),
+            InputKind::Bool => match value.to_ascii_lowercase().as_str() {
+                "y" | "yes" | "true" => return Ok("true".to_string()),
+                "n" | "no" | "false" => return Ok("false".to_string()),
+                _ => {}
+            },
+            InputKind::Choice => {}
+        }
+    }
+}
+
+/// Read a line from the terminal without echoing it
+fn read_hidden_line() -> std::io::Result<String> {
+    let stdin = std::io::stdin();
+    let original = termios::tcgetattr(&stdin)?;
+    let mut hidden = original.clone();
+    hidden.local_flags.remove(LocalFlags::ECHO);
+    termios::tcsetattr(&stdin, SetArg::TCSANOW, &hidden)?;
+    let mut line = String::new();
+    let result = stdin.read_line(&mut line);
+    termios::tcsetattr(&stdin, SetArg::TCSANOW, &original)?;
+    eprintln!();
+    line_or_eof(result, line)
+}
+
+/// The line read, or an error once the input has ended (e.g. after CTRL-d), so that asking
+/// again does not loop forever
+fn line_or_eof(read: io::Result<usize>, line: String) -> io::Result<String> {
+    match read? {
+        0 => Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "the input ended before a value was given",
+        )),
+        _ => Ok(line),
+    }
+}
+
+/// Ask on the terminal whether to run the remaining entries. Without a terminal to ask on, the
+/// batch stops.
+fn ask_to_continue() -> bool {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return false;
+    }
+    eprint!("Continue with the remaining entries? [y/N] ");
+    let mut answer = String::new();
+    stdin.read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+}
+
+fn show_history(id: Option<u64>) -> io::Result<i32> {
+    let runs = match history::load() {
+        Ok(runs) => runs,
+        Err(e) => {
+            eprintln!("osutil: failed to read the history: {e}");
+            return Ok(1);
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    let Some(id) = id else {
+        if runs.is_empty() {
+            eprintln!("No runs recorded yet");
+        }
+        for run in runs.iter().rev() {
+            writeln!(
+                out,
+                "#{:<4} {}  {:<12} {:>8}  {}",
+                run.id,
+                run.started(),
+                run.status(),
+                run.duration(),
+                run.entries.join(", ")
+            )?;
+        }
+        return Ok(0);
+    };
+
+    let Some(run) = runs.iter().find(|run| run.id == id) else {
+        eprintln!("osutil: no such run: {id}");
+        return Ok(1);
+    };
+    eprintln!("Run #{}: {}", run.id, run.entries.join(", "));
+    eprintln!(
+        "Started {}, {}, took {} (payload {})",
+        run.started(),
+        run.status(),
+        run.duration(),
+        run.payload_hash
+    );
+    if !run.has_log {
+        eprintln!("No output was recorded for this run");
+        return Ok(0);
+    }
+    match run.read_log() {
+        Ok(log) => {
+            out.write_all(&log)?;
+            Ok(0)
+        }
+        Err(e) => {
+            eprintln!("osutil: failed to read the log: {e}");
+            Ok(1)
+        }
+    }
+}
+
+/// The exit code of a command printing to stdout. A closed pipe, as with `osutil list | head`,
+/// ends the output quietly.
+fn write_status(result: io::Result<i32>) -> i32 {
+    match result {
+        Ok(code) => code,
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => 0,
+        Err(e) => {
+            eprintln!("osutil: failed to write the output: {e}");
+            1
+        }
+    }
+}
+
+/// Execute a command payload with inherited stdio, waiting for it to finish
+fn execute(command: &Command) -> std::io::Result<ExitStatus> {
+    let mut process = match command {
+        Command::Raw(prompt) => {
+            let mut process = process::Command::new("sh");
+            process.arg("-c").arg(prompt);
+            process
+        }
+        Command::LocalFile {
+            executable,
+            args,
+            file,
+            env,
+        } => {
+            let mut process = process::Command::new(executable);
+            process.args(args).envs(env);
+            if let Some(parent_directory) = file.parent() {
+                process.current_dir(parent_directory);
+            }
+            process
+        }
+        Command::None => panic!("Command::None was treated as a command"),
+    };
+    process.status()
+}
+
+/// Map an exit status to a shell-style exit code (128 + signal when killed by a signal)
+fn exit_code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
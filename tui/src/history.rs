@@ -0,0 +1,201 @@
+use osutil_core::Command;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+use time::{OffsetDateTime, UtcOffset, macros::format_description};
+
+/// One JSON object per line, oldest run first
+const HISTORY_FILE: &str = "history.jsonl";
+const LOGS_DIR: &str = "logs";
+/// Older runs, and their logs, are dropped once the history grows past this many runs
+const MAX_RUNS: usize = 100;
+
+/// A finished run, as recorded in the history
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub id: u64,
+    /// Names of the entries that ran, in order
+    pub entries: Vec<String>,
+    /// Hash of the scripts and commands that ran, to tell whether they changed since
+    pub payload_hash: String,
+    /// Unix timestamp, in seconds
+    pub started_at: i64,
+    /// Unix timestamp, in seconds
+    pub finished_at: i64,
+    pub exit_code: u32,
+    /// Set when the run was killed by a signal
+    pub signal: Option<String>,
+    /// Whether the output of the run was captured and saved
+    pub has_log: bool,
+}
+
+/// A run that has started, recorded in the history once it finishes
+pub struct PendingRun {
+    entries: Vec<String>,
+    payload_hash: String,
+    started_at: OffsetDateTime,
+}
+
+impl Run {
+    pub fn success(&self) -> bool {
+        self.exit_code == 0 && self.signal.is_none()
+    }
+
+    pub fn status(&self) -> String {
+        match &self.signal {
+            Some(signal) => format!("killed ({signal})"),
+            None if self.exit_code == 0 => "succeeded".to_string(),
+            None => format!("failed ({})", self.exit_code),
+        }
+    }
+
+    /// Start time in the local time zone, when it can be determined
+    pub fn started(&self) -> String {
+        let format = format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+        let offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+        OffsetDateTime::from_unix_timestamp(self.started_at)
+            .ok()
+            .and_then(|time| time.to_offset(offset).format(&format).ok())
+            .unwrap_or_else(|| self.started_at.to_string())
+    }
+
+    pub fn duration(&self) -> String {
+        let seconds = (self.finished_at - self.started_at).max(0);
+        match seconds {
+            0..60 => format!("{seconds}s"),
+            60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+            _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+        }
+    }
+
+    pub fn read_log(&self) -> io::Result<Vec<u8>> {
+        fs::read(log_path(self.id)?)
+    }
+}
+
+impl PendingRun {
+    /// Start recording a run of `commands`. The values of the `secrets` variables are left out
+    /// of its payload hash.
+    pub fn start(entries: Vec<String>, commands: &[&Command], secrets: &[String]) -> Self {
+        Self {
+            entries,
+            payload_hash: payload_hash(commands, secrets),
+            started_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Append the run to the history, saving the captured output when there is any
+    pub fn finish(
+        self,
+        exit_code: u32,
+        signal: Option<String>,
+        log: Option<&[u8]>,
+    ) -> io::Result<Run> {
+        fs::create_dir_all(osutil_core::state_file(LOGS_DIR)?)?;
+
+        let mut runs = load()?;
+        let run = Run {
+            id: runs.last().map_or(1, |run| run.id + 1),
+            entries: self.entries,
+            payload_hash: self.payload_hash,
+            started_at: self.started_at.unix_timestamp(),
+            finished_at: OffsetDateTime::now_utc().unix_timestamp(),
+            exit_code,
+            signal,
+            has_log: log.is_some(),
+        };
+        if let Some(log) = log {
+            fs::write(log_path(run.id)?, log)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(osutil_core::state_file(HISTORY_FILE)?)?;
+        writeln!(file, "{}", serde_json::to_string(&run)?)?;
+
+        runs.push(run.clone());
+        if runs.len() > MAX_RUNS {
+            let dropped = runs.len() - MAX_RUNS;
+            for old in runs.drain(..dropped) {
+                if let Ok(path) = log_path(old.id) {
+                    let _ = fs::remove_file(path);
+                }
+            }
+            let mut file = File::create(osutil_core::state_file(HISTORY_FILE)?)?;
+            for run in &runs {
+                writeln!(file, "{}", serde_json::to_string(run)?)?;
+            }
+        }
+        Ok(run)
+    }
+}
+
+/// Every recorded run, oldest first. Lines that cannot be parsed are skipped.
+pub fn load() -> io::Result<Vec<Run>> {
+    let file = match File::open(osutil_core::state_file(HISTORY_FILE)?) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut runs = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(run) = serde_json::from_str(&line?) {
+            runs.push(run);
+        }
+    }
+    Ok(runs)
+}
+
+/// The run with the given id, or the most recent run when there is no id
+pub fn find_run(id: Option<u64>) -> io::Result<Option<Run>> {
+    let runs = load()?;
+    Ok(match id {
+        Some(id) => runs.into_iter().find(|run| run.id == id),
+        None => runs.into_iter().last(),
+    })
+}
+
+fn log_path(id: u64) -> io::Result<PathBuf> {
+    Ok(osutil_core::state_file(LOGS_DIR)?.join(format!("{id}.log")))
+}
+
+/// FNV-1a hash of everything that was run: the raw commands, and the executable, arguments,
+/// contents and variables of scripts. Only the names of the `secrets` variables are hashed.
+fn payload_hash(commands: &[&Command], secrets: &[String]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut feed = |bytes: &[u8]| {
+        for byte in bytes.iter().chain(&[0]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    for command in commands {
+        match command {
+            Command::Raw(prompt) => feed(prompt.as_bytes()),
+            Command::LocalFile {
+                executable,
+                args,
+                file,
+                env,
+            } => {
+                feed(executable.as_bytes());
+                for arg in args {
+                    feed(arg.as_bytes());
+                }
+                for (key, value) in env {
+                    feed(key.as_bytes());
+                    if !secrets.contains(key) {
+                        feed(value.as_bytes());
+                    }
+                }
+                feed(&fs::read(file).unwrap_or_default());
+            }
+            Command::None => {}
+        }
+    }
+    format!("{hash:016x}")
+}
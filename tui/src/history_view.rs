@@ -0,0 +1,214 @@
+use crate::{
+    float::FloatContent,
+    hint::Shortcut,
+    history::{self, Run},
+    shortcuts,
+    theme::Theme,
+};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind},
+    prelude::*,
+    symbols::border,
+    widgets::{Block, Clear, List, ListState, Paragraph, Wrap},
+};
+use tui_term::widget::{Cursor, PseudoTerminal};
+use vt100::Parser;
+
+/// Browse the recorded runs, newest first, and re-open their logs
+pub struct HistoryView {
+    runs: Vec<Run>,
+    /// Set when the history could not be read
+    error: Option<String>,
+    selection: ListState,
+    /// The log of the selected run, while it is open
+    log: Option<LogView>,
+}
+
+struct LogView {
+    title: String,
+    status: String,
+    buffer: Vec<u8>,
+    scroll_offset: usize,
+}
+
+impl HistoryView {
+    pub fn new() -> Self {
+        let (mut runs, error) = match history::load() {
+            Ok(runs) => (runs, None),
+            Err(e) => (Vec::new(), Some(format!("Failed to read the history: {e}"))),
+        };
+        runs.reverse();
+        let selection = ListState::default().with_selected((!runs.is_empty()).then_some(0));
+        Self {
+            runs,
+            error,
+            selection,
+            log: None,
+        }
+    }
+
+    fn selected_run(&self) -> Option<&Run> {
+        self.selection
+            .selected()
+            .and_then(|index| self.runs.get(index))
+    }
+
+    fn open_log(&mut self) {
+        let Some(run) = self.selected_run() else {
+            return;
+        };
+        let buffer = match run.read_log() {
+            Ok(buffer) => buffer,
+            Err(_) if !run.has_log => b"No output was recorded for this run.".to_vec(),
+            Err(e) => format!("Failed to read the log: {e}").into_bytes(),
+        };
+        self.log = Some(LogView {
+            title: format!(" Run #{}: {} ", run.id, run.entries.join(", ")),
+            status: format!(
+                " {}, {}, took {} ",
+                run.started(),
+                run.status(),
+                run.duration()
+            ),
+            buffer,
+            scroll_offset: 0,
+        });
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" History ")
+            .title_alignment(Alignment::Center)
+            .title_style(Style::default().reversed());
+
+        if self.runs.is_empty() {
+            let message = self.error.as_deref().unwrap_or("No runs recorded yet");
+            let paragraph = Paragraph::new(message)
+                .block(block)
+                .wrap(Wrap { trim: false });
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        let items = self.runs.iter().map(|run| {
+            let status_color = if run.success() {
+                theme.success_color()
+            } else {
+                theme.fail_color()
+            };
+            Line::from(vec![
+                Span::raw(format!("#{:<4} {}  ", run.id, run.started())),
+                Span::styled(format!("{:<12}", run.status()), status_color),
+                Span::raw(format!(
+                    " {:>8}  {}",
+                    run.duration(),
+                    run.entries.join(", ")
+                )),
+            ])
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.selection);
+    }
+}
+
+impl LogView {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title_top(Line::from(self.title.as_str()).centered())
+            .title_style(Style::default().reversed())
+            .title_bottom(Line::from(self.status.as_str()).centered());
+        let size = block.inner(area);
+
+        let mut parser = Parser::new(size.height, size.width, 1000);
+        parser.process(&self.buffer);
+        parser.screen_mut().set_scrollback(self.scroll_offset);
+        let mut cursor = Cursor::default();
+        cursor.hide();
+        let pseudo_term = PseudoTerminal::new(parser.screen())
+            .block(block)
+            .cursor(cursor);
+        frame.render_widget(pseudo_term, area);
+    }
+}
+
+impl FloatContent for HistoryView {
+    fn draw(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        frame.render_widget(Clear, area);
+        match &mut self.log {
+            Some(log) => log.draw(frame, area),
+            None => self.draw_list(frame, area, theme),
+        }
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent) -> bool {
+        match (&mut self.log, event.kind) {
+            (Some(log), MouseEventKind::ScrollUp) => {
+                log.scroll_offset = log.scroll_offset.saturating_add(1);
+            }
+            (Some(log), MouseEventKind::ScrollDown) => {
+                log.scroll_offset = log.scroll_offset.saturating_sub(1);
+            }
+            (None, MouseEventKind::ScrollUp) => self.selection.select_previous(),
+            (None, MouseEventKind::ScrollDown) => self.selection.select_next(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Closes the log when one is open, otherwise returns true to close the window
+    fn handle_key_event(&mut self, key: &KeyEvent) -> bool {
+        use KeyCode::{Char, Down, Enter, Esc, Left, PageDown, PageUp, Right, Up};
+        if let Some(log) = &mut self.log {
+            match key.code {
+                Char('k') | Up => log.scroll_offset = log.scroll_offset.saturating_add(1),
+                Char('j') | Down => log.scroll_offset = log.scroll_offset.saturating_sub(1),
+                PageUp => log.scroll_offset = log.scroll_offset.saturating_add(10),
+                PageDown => log.scroll_offset = log.scroll_offset.saturating_sub(10),
+                Char('q') | Esc | Enter | Char('h') | Left => self.log = None,
+                _ => {}
+            }
+            return false;
+        }
+        match key.code {
+            Char('j') | Down => self.selection.select_next(),
+            Char('k') | Up => self.selection.select_previous(),
+            Enter | Char('l') | Right => self.open_log(),
+            Char('q') | Esc => return true,
+            _ => {}
+        }
+        false
+    }
+
+    // Closing is handled in `handle_key_event`, so that leaving a log does not close the window
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>) {
+        if self.log.is_some() {
+            (
+                "Run log",
+                shortcuts!(
+                    ("Scroll up", ["k", "Up", "Page up"]),
+                    ("Scroll down", ["j", "Down", "Page down"]),
+                    ("Back to history", ["Enter", "q", "Esc"]),
+                ),
+            )
+        } else {
+            (
+                "History",
+                shortcuts!(
+                    ("Open log", ["Enter", "l", "Right"]),
+                    ("Select item above", ["k", "Up"]),
+                    ("Select item below", ["j", "Down"]),
+                    ("Close window", ["q", "Esc"]),
+                ),
+            )
+        }
+    }
+}
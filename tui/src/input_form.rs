@@ -0,0 +1,260 @@
+use crate::{
+    float::FloatContent,
+    hint::Shortcut,
+    running_command::{RunningCommand, secret_names},
+    shortcuts,
+    theme::Theme,
+};
+use osutil_core::{Command, FailurePolicy, Input, InputKind, ListNode};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind},
+    prelude::*,
+    symbols::border,
+    widgets::{Block, Clear, List, ListItem, ListState},
+};
+use std::rc::Rc;
+
+/// Asks for the inputs of the selected entries, then runs them with the values exported
+pub struct InputForm {
+    fields: Vec<Field>,
+    selection: ListState,
+    /// The entries to run once the form is submitted
+    nodes: Vec<Rc<ListNode>>,
+    on_failure: FailurePolicy,
+    submitted: bool,
+}
+
+struct Field {
+    /// Index of the entry in `nodes`
+    node: usize,
+    input: Input,
+    value: String,
+}
+
+impl InputForm {
+    pub fn new(nodes: Vec<Rc<ListNode>>, on_failure: FailurePolicy) -> Self {
+        let fields: Vec<Field> = nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, node)| node.unanswered_inputs().map(move |input| (index, input)))
+            .map(|(node, input)| {
+                let value = input.initial_value().unwrap_or_else(|| match input.kind {
+                    InputKind::Choice => input.choices.first().cloned().unwrap_or_default(),
+                    InputKind::Bool => "false".to_string(),
+                    InputKind::Text | InputKind::Password => String::new(),
+                });
+                Field {
+                    node,
+                    input: input.clone(),
+                    value,
+                }
+            })
+            .collect();
+        Self {
+            fields,
+            selection: ListState::default().with_selected(Some(0)),
+            nodes,
+            on_failure,
+            submitted: false,
+        }
+    }
+
+    fn selected_field(&mut self) -> Option<&mut Field> {
+        self.fields.get_mut(self.selection.selected()?)
+    }
+
+    fn select_next(&mut self) {
+        let next = self.selection.selected().map_or(0, |index| index + 1);
+        self.selection
+            .select(Some(next.min(self.fields.len().saturating_sub(1))));
+    }
+
+    fn select_previous(&mut self) {
+        let previous = self
+            .selection
+            .selected()
+            .map_or(0, |index| index.saturating_sub(1));
+        self.selection.select(Some(previous));
+    }
+}
+
+impl Field {
+    /// Move to the next or previous value of a choice or bool input
+    fn cycle(&mut self, forward: bool) {
+        match self.input.kind {
+            InputKind::Bool => {
+                self.value = if self.value == "true" {
+                    "false"
+                } else {
+                    "true"
+                }
+                .to_string();
+            }
+            InputKind::Choice if !self.input.choices.is_empty() => {
+                let choices = &self.input.choices;
+                let count = choices.len();
+                let index = choices.iter().position(|choice| *choice == self.value);
+                let next = match (index, forward) {
+                    (None, _) => 0,
+                    (Some(index), true) => (index + 1) % count,
+                    (Some(index), false) => (index + count - 1) % count,
+                };
+                self.value = choices[next].clone();
+            }
+            _ => {}
+        }
+    }
+
+    fn display_value(&self) -> String {
+        match self.input.kind {
+            InputKind::Text => self.value.clone(),
+            InputKind::Password => "•".repeat(self.value.chars().count()),
+            InputKind::Choice => format!("◀ {} ▶", self.value),
+            InputKind::Bool if self.value == "true" => "◀ yes ▶".to_string(),
+            InputKind::Bool => "◀ no ▶".to_string(),
+        }
+    }
+}
+
+impl FloatContent for InputForm {
+    fn draw(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" Inputs ")
+            .title_bottom(Line::from(vec![
+                Span::raw(" ["),
+                Span::styled("Enter", Style::default().fg(theme.success_color())),
+                Span::raw("] to run ["),
+                Span::styled("Esc", Style::default().fg(theme.fail_color())),
+                Span::raw("] to cancel "),
+            ]))
+            .title_alignment(Alignment::Center)
+            .title_style(Style::default().bold());
+
+        // Name the entry of each input when there is more than one entry
+        let show_entry = self.nodes.len() > 1;
+        let selected = self.selection.selected();
+        let items = self.fields.iter().enumerate().map(|(index, field)| {
+            let label = if show_entry {
+                format!("{} › {}", self.nodes[field.node].name, field.input.prompt)
+            } else {
+                field.input.prompt.clone()
+            };
+            let value_style = if selected == Some(index) {
+                Style::default().fg(theme.focused_color())
+            } else {
+                Style::default()
+            };
+            let mut value = Line::styled(format!("  {}", field.display_value()), value_style);
+            if selected == Some(index)
+                && matches!(field.input.kind, InputKind::Text | InputKind::Password)
+            {
+                value.push_span(Span::styled("▏", value_style));
+            }
+            ListItem::new(Text::from(vec![
+                Line::from(label).bold(),
+                value,
+                Line::default(),
+            ]))
+        });
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(List::new(items).block(block), area, &mut self.selection);
+    }
+
+    fn handle_mouse_event(&mut self, event: &MouseEvent) -> bool {
+        match event.kind {
+            MouseEventKind::ScrollDown => self.select_next(),
+            MouseEventKind::ScrollUp => self.select_previous(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Returns true once the form is submitted or cancelled
+    fn handle_key_event(&mut self, key: &KeyEvent) -> bool {
+        use KeyCode::{BackTab, Backspace, Char, Down, Enter, Esc, Left, Right, Tab, Up};
+        match key.code {
+            Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return true,
+            Esc => return true,
+            Enter => {
+                self.submitted = true;
+                return true;
+            }
+            Down | Tab => self.select_next(),
+            Up | BackTab => self.select_previous(),
+            Left | Right | Char(' ') => {
+                if let Some(field) = self.selected_field() {
+                    match field.input.kind {
+                        InputKind::Choice | InputKind::Bool => field.cycle(key.code != Left),
+                        InputKind::Text | InputKind::Password => {
+                            if key.code == Char(' ') {
+                                field.value.push(' ');
+                            }
+                        }
+                    }
+                }
+            }
+            Char(c) => {
+                if let Some(field) = self.selected_field()
+                    && matches!(field.input.kind, InputKind::Text | InputKind::Password)
+                {
+                    field.value.push(c);
+                }
+            }
+            Backspace => {
+                if let Some(field) = self.selected_field() {
+                    field.value.pop();
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    // Closing is handled in `handle_key_event`, so that typed characters do not close the form
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>) {
+        (
+            "Inputs",
+            shortcuts!(
+                ("Run with these values", ["Enter"]),
+                ("Cancel", ["Esc"]),
+                ("Next field", ["Tab", "Down"]),
+                ("Previous field", ["Shift-Tab", "Up"]),
+                ("Change the value", ["Left", "Right", "Space"]),
+            ),
+        )
+    }
+
+    /// Runs the entries once the form is submitted
+    fn followup(&mut self) -> Option<Box<dyn FloatContent>> {
+        if !self.submitted {
+            return None;
+        }
+        let commands: Vec<Command> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let values = self
+                    .fields
+                    .iter()
+                    .filter(|field| field.node == index)
+                    .map(|field| (field.input.name.clone(), field.value.clone()));
+                node.command.with_env(values)
+            })
+            .collect();
+        let commands: Vec<&Command> = commands.iter().collect();
+        let names: Vec<String> = self.nodes.iter().map(|node| node.name.clone()).collect();
+        Some(Box::new(RunningCommand::new_with_names(
+            &commands,
+            &names,
+            &secret_names(&self.nodes),
+            self.on_failure,
+        )))
+    }
+}
@@ -1,10 +1,18 @@
 mod cli;
 mod confirmation;
+mod entry_path;
+mod favorites;
 mod filter;
+mod flag_filter;
 mod float;
 mod floating_text;
+mod headless;
 mod hint;
+mod history;
+mod history_view;
+mod input_form;
 mod logo;
+mod recent;
 mod root;
 mod running_command;
 mod state;
@@ -61,7 +69,17 @@ impl Drop for TerminalCleanupGuard {
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let mut state = AppState::new(args.clone());
+    if let Some(command) = &args.command {
+        std::process::exit(headless::run(&args, command));
+    }
+
+    let mut state = match AppState::new(args.clone()) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("osutil: {e}");
+            std::process::exit(1);
+        }
+    };
 
     stdout().execute(EnterAlternateScreen)?;
     if args.mouse {
@@ -0,0 +1,109 @@
+use crate::entry_path::{find_entry, list_tab};
+use osutil_core::Tab;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, ErrorKind},
+};
+use time::OffsetDateTime;
+
+/// Name of the tab listing the entries run most
+pub const TAB_NAME: &str = "Recent";
+const RECENT_FILE: &str = "recent.json";
+/// Number of entries listed in the Recent tab
+const MAX_LISTED: usize = 20;
+/// Entries with the lowest score are forgotten once more than this many have run
+const MAX_KEPT: usize = 100;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// How often and how lately each entry was run, by where the entry is
+#[derive(Default)]
+pub struct Recent {
+    entries: Vec<RecentEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecentEntry {
+    /// The name of the entry's tab, followed by its directories and its own name
+    path: Vec<String>,
+    runs: u32,
+    /// Unix timestamp, in seconds
+    last_run: i64,
+}
+
+impl RecentEntry {
+    /// Frecency: the number of runs, weighted up for entries run in the last day and down for
+    /// entries not run for a week or more
+    fn score(&self, now: i64) -> f64 {
+        let runs = f64::from(self.runs);
+        let age = now - self.last_run;
+        if age < HOUR {
+            runs * 4.0
+        } else if age < DAY {
+            runs * 2.0
+        } else if age < WEEK {
+            runs
+        } else {
+            runs / 4.0
+        }
+    }
+}
+
+impl Recent {
+    /// The runs recorded by earlier sessions
+    pub fn load() -> io::Result<Self> {
+        let entries = match fs::read_to_string(osutil_core::state_file(RECENT_FILE)?) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { entries })
+    }
+
+    /// Count one more run of each entry at `paths`, and save the change
+    pub fn record(&mut self, paths: Vec<Vec<String>>) -> io::Result<()> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        for path in paths {
+            match self.entries.iter_mut().find(|entry| entry.path == path) {
+                Some(entry) => {
+                    entry.runs += 1;
+                    entry.last_run = now;
+                }
+                None => self.entries.push(RecentEntry {
+                    path,
+                    runs: 1,
+                    last_run: now,
+                }),
+            }
+        }
+        self.sort(now);
+        self.entries.truncate(MAX_KEPT);
+
+        let path = osutil_core::state_file(RECENT_FILE)?;
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, serde_json::to_string_pretty(&self.entries)?)
+    }
+
+    fn sort(&mut self, now: i64) {
+        self.entries.sort_by(|a, b| {
+            b.score(now)
+                .total_cmp(&a.score(now))
+                .then(b.last_run.cmp(&a.last_run))
+        });
+    }
+
+    /// The Recent tab, listing the entries found in `tabs` with the highest score first. Entries
+    /// that are not there anymore are left out.
+    pub fn tab(&mut self, tabs: &[Tab]) -> Tab {
+        self.sort(OffsetDateTime::now_utc().unix_timestamp());
+        let nodes = self
+            .entries
+            .iter()
+            .filter_map(|entry| find_entry(tabs, &entry.path))
+            .take(MAX_LISTED);
+        list_tab(TAB_NAME, tabs, nodes)
+    }
+}
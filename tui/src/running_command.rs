@@ -1,6 +1,8 @@
-use crate::{float::FloatContent, hint::Shortcut, shortcuts, theme::Theme};
-use oneshot::{Receiver, channel};
-use osutil_core::Command;
+use crate::{
+    float::FloatContent, floating_text::FloatingText, hint::Shortcut, history::PendingRun,
+    shortcuts, theme::Theme,
+};
+use osutil_core::{Command, FailurePolicy, ListNode};
 use portable_pty::{
     ChildKiller, CommandBuilder, ExitStatus, MasterPty, NativePtySystem, PtySize, PtySystem,
 };
@@ -8,16 +10,20 @@ use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind},
     prelude::*,
     symbols::border,
-    widgets::Block,
+    widgets::{Block, Clear, List},
 };
 use std::{
+    borrow::Cow,
     fs::File,
     io::{Read, Result, Write},
+    rc::Rc,
     sync::{
         Arc, Mutex,
         atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
     },
     thread::JoinHandle,
+    time::{Duration, Instant},
 };
 use time::{OffsetDateTime, macros::format_description};
 use tui_term::widget::PseudoTerminal;
@@ -28,10 +34,20 @@ pub struct RunningCommand {
     buffer: Arc<Mutex<Vec<u8>>>,
     /// A handle for the thread running the command
     command_thread: Option<JoinHandle<ExitStatus>>,
-    /// A handle to kill the running process; it's an option because it can only be used once
-    child_killer: Option<Receiver<Box<dyn ChildKiller + Send + Sync>>>,
+    /// A handle to kill the process of the current step, set while a step is running
+    child_killer: Arc<Mutex<Option<Box<dyn ChildKiller + Send + Sync>>>>,
+    /// Set when the command is killed, so the remaining steps are not started
+    cancelled: Arc<AtomicBool>,
+    /// Names of the steps, one per command
+    step_names: Vec<String>,
+    /// State of each step, updated by the command thread
+    steps: Arc<Mutex<Vec<StepState>>>,
+    /// Set while the command thread waits to be told whether to go on after a failed step
+    awaiting_decision: Arc<AtomicBool>,
+    /// Tells the command thread whether to run the remaining steps
+    decision: Sender<bool>,
     /// A join handle for the thread that reads command output and sends it to the main thread
-    _reader_thread: JoinHandle<()>,
+    reader_thread: JoinHandle<()>,
     /// Virtual terminal (pty) handle, used for resizing the pty
     pty_master: Box<dyn MasterPty + Send>,
     /// Used for sending keys to the emulated terminal
@@ -40,16 +56,68 @@ pub struct RunningCommand {
     status: Option<ExitStatus>,
     log_path: Option<String>,
     scroll_offset: usize,
+    /// Recorded in the run history once the command exits
+    pending_run: Option<PendingRun>,
+    /// Id of the run in the history, once recorded
+    history_id: Option<u64>,
+}
+
+#[derive(Clone, PartialEq)]
+enum StepState {
+    Pending,
+    Running,
+    Succeeded,
+    /// Failed, with a description of the exit status
+    Failed(String),
+    /// Not started because an earlier step failed or the command was killed
+    Skipped,
 }
 
 impl FloatContent for RunningCommand {
     fn draw(&mut self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        // Show the steps next to the terminal when there is more than one
+        let area = if self.step_names.len() > 1 {
+            let longest_name = self.step_names.iter().map(|name| name.len()).max();
+            let sidebar_width = (longest_name.unwrap_or(0) as u16 + 6).min(area.width / 3);
+            let [sidebar, terminal] =
+                Layout::horizontal([Constraint::Length(sidebar_width), Constraint::Min(1)])
+                    .areas(area);
+            self.draw_steps(frame, sidebar, theme);
+            terminal
+        } else {
+            area
+        };
+
         // Define the block for the terminal display
-        let block = if !self.is_finished() {
+        let block = if self.is_awaiting_decision() {
+            let title = match self.failed_step() {
+                Some(index) => format!(
+                    "{} failed. Continue with the remaining steps? [y/n]",
+                    self.step_names[index]
+                ),
+                None => "A step failed. Continue with the remaining steps? [y/n]".to_string(),
+            };
+            Block::bordered()
+                .border_set(border::ROUNDED)
+                .title_top(
+                    Line::styled(title, Style::default().fg(theme.fail_color()).reversed())
+                        .centered(),
+                )
+                .title_bottom(Line::from("Press Ctrl-C to stop here"))
+        } else if !self.is_finished() {
+            let running_title = match self.current_step() {
+                Some(index) if self.step_names.len() > 1 => format!(
+                    "Running step {}/{}: {}",
+                    index + 1,
+                    self.step_names.len(),
+                    self.step_names[index]
+                ),
+                _ => "Running the command....".to_string(),
+            };
             // Display a block indicating the command is running
             Block::bordered()
                 .border_set(border::ROUNDED)
-                .title_top(Line::from("Running the command....").centered())
+                .title_top(Line::from(running_title).centered())
                 .title_style(Style::default().reversed())
                 .title_bottom(Line::from("Press Ctrl-C to KILL the command"))
         } else {
@@ -60,16 +128,22 @@ impl FloatContent for RunningCommand {
                     Style::default().fg(theme.success_color()).reversed(),
                 )
             } else {
-                Line::styled(
-                    "FAILED! Press <ENTER> to close this window",
-                    Style::default().fg(theme.fail_color()).reversed(),
-                )
+                let title = match self.failed_step() {
+                    Some(index) if self.step_names.len() > 1 => format!(
+                        "FAILED at {}! Press <ENTER> to close this window",
+                        self.step_names[index]
+                    ),
+                    _ => "FAILED! Press <ENTER> to close this window".to_string(),
+                };
+                Line::styled(title, Style::default().fg(theme.fail_color()).reversed())
             };
 
-            let log_path = if let Some(log_path) = &self.log_path {
-                Line::from(format!(" Log saved: {log_path} "))
-            } else {
-                Line::from(" Press 'l' to save command log ")
+            let log_path = match (&self.log_path, self.history_id) {
+                (Some(log_path), _) => Line::from(format!(" Log saved: {log_path} ")),
+                (None, Some(id)) => Line::from(format!(
+                    " Recorded as run #{id} in history. Press 'l' to save command log "
+                )),
+                (None, None) => Line::from(" Press 'l' to save command log "),
             };
 
             Block::bordered()
@@ -108,6 +182,15 @@ impl FloatContent for RunningCommand {
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.kill_child();
             }
+            // Answer whether to go on after a failed step
+            KeyCode::Char('y') | KeyCode::Char('Y') if self.is_awaiting_decision() => {
+                let _ = self.decision.send(true);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc
+                if self.is_awaiting_decision() =>
+            {
+                let _ = self.decision.send(false);
+            }
             // Pass Enter key to running command for user input
             KeyCode::Enter if !self.is_finished() => {
                 self.handle_passthrough_key_event(key);
@@ -143,7 +226,17 @@ impl FloatContent for RunningCommand {
     }
 
     fn get_shortcut_list(&self) -> (&str, Box<[Shortcut]>) {
-        if self.is_finished() {
+        if self.is_awaiting_decision() {
+            (
+                "Failed step",
+                shortcuts!(
+                    ("Continue with the remaining steps", ["y"]),
+                    ("Stop here", ["n", "Esc", "CTRL-c"]),
+                    ("Scroll up", ["Page up"]),
+                    ("Scroll down", ["Page down"]),
+                ),
+            )
+        } else if self.is_finished() {
             (
                 "Finished command",
                 shortcuts!(
@@ -164,6 +257,37 @@ impl FloatContent for RunningCommand {
             )
         }
     }
+
+    /// A summary of every step's outcome, for batches
+    fn followup(&mut self) -> Option<Box<dyn FloatContent>> {
+        if self.step_names.len() < 2 {
+            return None;
+        }
+        let steps = self.steps.lock().unwrap();
+        let count = |wanted: fn(&StepState) -> bool| steps.iter().filter(|s| wanted(s)).count();
+        let mut summary = format!(
+            "{} succeeded, {} failed, {} skipped\n\n",
+            count(|state| *state == StepState::Succeeded),
+            count(|state| matches!(state, StepState::Failed(_))),
+            count(|state| *state == StepState::Skipped),
+        );
+        let width = self
+            .step_names
+            .iter()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, state) in self.step_names.iter().zip(steps.iter()) {
+            let outcome = match state {
+                StepState::Succeeded => "✓ succeeded".to_string(),
+                StepState::Failed(status) => format!("✗ failed ({status})"),
+                StepState::Skipped => "- skipped".to_string(),
+                StepState::Pending | StepState::Running => "- not finished".to_string(),
+            };
+            summary.push_str(&format!("{name:<width$}  {outcome}\n"));
+        }
+        Some(Box::new(FloatingText::new(summary, "Summary", false)))
+    }
 }
 
 pub static TERMINAL_UPDATED: AtomicBool = AtomicBool::new(true);
@@ -173,57 +297,139 @@ fn get_shell() -> (&'static str, &'static str) {
     ("sh", "-c")
 }
 
-impl RunningCommand {
-    pub fn new(commands: &[&Command]) -> Self {
-        let pty_system = NativePtySystem::default();
+fn describe_status(status: &ExitStatus) -> String {
+    match status.signal() {
+        Some(signal) => format!("killed by {signal}"),
+        None => format!("exit code {}", status.exit_code()),
+    }
+}
 
-        // Get platform-specific shell
-        let (shell, shell_arg) = get_shell();
-
-        // Build the command based on the provided Command enum variant
-        let mut cmd: CommandBuilder = CommandBuilder::new(shell);
-        cmd.arg(shell_arg);
-
-        // Set environment variables needed for interactive TUI tools
-        cmd.env("TERM", "xterm-256color");
-        cmd.env("COLORTERM", "truecolor");
-        cmd.env("FORCE_COLOR", "1");
-        cmd.env("NO_COLOR", "");
-        // Mark that we are running under the OSutil TUI so scripts can detect this
-        cmd.env("OSUTIL_TUI_MODE", "1");
-
-        // Build the script from all commands
-        let mut script = String::new();
-
-        for command in commands {
-            match command {
-                Command::Raw(prompt) => {
-                    script.push_str(prompt);
-                    script.push('\n');
-                }
-                Command::LocalFile {
-                    executable,
-                    args,
-                    file,
-                } => {
-                    // Change to the script's directory first
-                    if let Some(parent_directory) = file.parent() {
-                        script.push_str(&format!("cd '{}'\n", parent_directory.display()));
-                    }
+/// Build the shell script running a single command
+fn build_script(command: &Command) -> String {
+    let mut script = String::new();
+    match command {
+        Command::Raw(prompt) => {
+            script.push_str(prompt);
+            script.push('\n');
+        }
+        Command::LocalFile {
+            executable,
+            args,
+            file,
+            ..
+        } => {
+            // Change to the script's directory first
+            if let Some(parent_directory) = file.parent() {
+                let directory = parent_directory.to_string_lossy();
+                script.push_str(&format!("cd {}\n", shell_quote(&directory)));
+            }
 
-                    // Add the command
-                    script.push_str(executable);
-                    for arg in args {
-                        script.push(' ');
-                        script.push_str(arg);
-                    }
-                    script.push('\n');
+            // Add the command
+            script.push_str(&shell_quote(executable));
+            for arg in args {
+                script.push(' ');
+                script.push_str(&shell_quote(arg));
+            }
+            script.push('\n');
+        }
+        Command::None => panic!("Command::None was treated as a command"),
+    }
+    script
+}
+
+/// Quote a word for `sh` so that it is passed through unchanged. Words made only of characters
+/// the shell does not treat specially are left as they are.
+fn shell_quote(word: &str) -> Cow<'_, str> {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if !word.is_empty() && word.chars().all(is_plain) {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(format!("'{}'", word.replace('\'', r"'\''")))
+    }
+}
+
+/// Names of the variables holding passwords for any of the entries. Their values are neither
+/// shown nor recorded.
+pub fn secret_names(nodes: &[Rc<ListNode>]) -> Vec<String> {
+    nodes
+        .iter()
+        .flat_map(|node| node.password_inputs())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The scripts of all steps as they would run, each under a comment naming its step. Every step
+/// runs in its own `sh -c` process. The values of the `secrets` variables are hidden.
+pub fn assemble_script(
+    commands: &[&Command],
+    script_names: &[String],
+    secrets: &[String],
+) -> String {
+    let mut script = String::new();
+    for (index, (command, name)) in commands.iter().zip(script_names).enumerate() {
+        if index > 0 {
+            script.push('\n');
+        }
+        if commands.len() > 1 {
+            script.push_str(&format!(
+                "# Step {}/{}: {name}\n",
+                index + 1,
+                commands.len()
+            ));
+        } else {
+            script.push_str(&format!("# {name}\n"));
+        }
+        // The entry's variables are set on the process rather than written into the script
+        if let Command::LocalFile { env, .. } = command {
+            for (key, value) in env {
+                if secrets.contains(key) {
+                    script.push_str(&format!("export {key}=<hidden>\n"));
+                } else {
+                    script.push_str(&format!("export {key}={}\n", shell_quote(value)));
                 }
-                Command::None => panic!("Command::None was treated as a command"),
             }
         }
+        script.push_str(&build_script(command));
+    }
+    script
+}
+
+/// Build the process for a single step
+fn build_command(command: &Command) -> CommandBuilder {
+    // Get platform-specific shell
+    let (shell, shell_arg) = get_shell();
+
+    let mut cmd: CommandBuilder = CommandBuilder::new(shell);
+    cmd.arg(shell_arg);
+
+    // Set environment variables needed for interactive TUI tools
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+    cmd.env("FORCE_COLOR", "1");
+    cmd.env("NO_COLOR", "");
+    // Mark that we are running under the OSutil TUI so scripts can detect this
+    cmd.env("OSUTIL_TUI_MODE", "1");
+    if let Command::LocalFile { env, .. } = command {
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+    }
+
+    cmd.arg(build_script(command));
+    cmd
+}
 
-        cmd.arg(script);
+impl RunningCommand {
+    /// Run the commands one after another, each in its own process, as steps labelled with the
+    /// given names. The run is recorded in the history under the same names, leaving out the
+    /// values of the `secrets` variables.
+    pub fn new_with_names(
+        commands: &[&Command],
+        script_names: &[String],
+        secrets: &[String],
+        on_failure: FailurePolicy,
+    ) -> Self {
+        let pty_system = NativePtySystem::default();
 
         // Open a pseudo-terminal with initial size
         let pair = pty_system
@@ -235,20 +441,94 @@ impl RunningCommand {
             })
             .unwrap();
 
-        let (tx, rx) = channel();
-        // Thread waiting for the child to complete
-        let command_handle = std::thread::spawn(move || {
-            let mut child = pair.slave.spawn_command(cmd).unwrap();
-            let killer = child.clone_killer();
-            tx.send(killer).unwrap();
-            child.wait().unwrap()
-        });
-
-        let mut reader = pair.master.try_clone_reader().unwrap();
-
         // A buffer, shared between the thread that reads the command output, and the main thread.
         // The main thread only reads the contents
         let command_buffer: Arc<Mutex<Vec<u8>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let builders: Vec<CommandBuilder> = commands.iter().map(|c| build_command(c)).collect();
+        let steps = Arc::new(Mutex::new(vec![StepState::Pending; builders.len()]));
+        let child_killer: Arc<Mutex<Option<Box<dyn ChildKiller + Send + Sync>>>> =
+            Arc::new(Mutex::new(None));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let awaiting_decision = Arc::new(AtomicBool::new(false));
+        let (decision, decisions) = mpsc::channel();
+
+        // Thread running the steps in order. The first failure is the overall exit status; what
+        // happens to the steps after it depends on the failure policy.
+        let command_handle = {
+            let steps = steps.clone();
+            let child_killer = child_killer.clone();
+            let cancelled = cancelled.clone();
+            let awaiting_decision = awaiting_decision.clone();
+            let command_buffer = command_buffer.clone();
+            std::thread::spawn(move || {
+                let step_count = builders.len();
+                let mut result = ExitStatus::with_exit_code(0);
+                let mut stopped = false;
+                for (index, cmd) in builders.into_iter().enumerate() {
+                    let set_state = |state| {
+                        steps.lock().unwrap()[index] = state;
+                        TERMINAL_UPDATED.store(true, Ordering::Release);
+                    };
+                    if stopped || cancelled.load(Ordering::Acquire) {
+                        set_state(StepState::Skipped);
+                        continue;
+                    }
+
+                    set_state(StepState::Running);
+                    let status = match pair.slave.spawn_command(cmd) {
+                        Ok(mut child) => {
+                            *child_killer.lock().unwrap() = Some(child.clone_killer());
+                            // The command may have been killed before the killer was available
+                            if cancelled.load(Ordering::Acquire) {
+                                let _ = child.kill();
+                            }
+                            let status = child
+                                .wait()
+                                .unwrap_or_else(|_| ExitStatus::with_exit_code(1));
+                            child_killer.lock().unwrap().take();
+                            status
+                        }
+                        Err(e) => {
+                            let message = format!("\r\nFailed to start the command: {e}\r\n");
+                            command_buffer
+                                .lock()
+                                .unwrap()
+                                .extend_from_slice(message.as_bytes());
+                            ExitStatus::with_exit_code(127)
+                        }
+                    };
+
+                    if status.success() {
+                        set_state(StepState::Succeeded);
+                        continue;
+                    }
+                    set_state(StepState::Failed(describe_status(&status)));
+                    if result.success() {
+                        result = status;
+                    }
+
+                    let is_last = index + 1 == step_count;
+                    stopped = match on_failure {
+                        FailurePolicy::Stop => true,
+                        FailurePolicy::Continue => false,
+                        FailurePolicy::Prompt if is_last => true,
+                        FailurePolicy::Prompt => {
+                            awaiting_decision.store(true, Ordering::Release);
+                            TERMINAL_UPDATED.store(true, Ordering::Release);
+                            let go_on = decisions.recv().unwrap_or(false);
+                            awaiting_decision.store(false, Ordering::Release);
+                            TERMINAL_UPDATED.store(true, Ordering::Release);
+                            !go_on
+                        }
+                    };
+                }
+                result
+            })
+        };
+
+        let mut reader = pair.master.try_clone_reader().unwrap();
+
         TERMINAL_UPDATED.store(true, Ordering::Release);
         let reader_handle = {
             let command_buffer = command_buffer.clone();
@@ -279,20 +559,67 @@ impl RunningCommand {
         Self {
             buffer: command_buffer,
             command_thread: Some(command_handle),
-            child_killer: Some(rx),
-            _reader_thread: reader_handle,
+            child_killer,
+            cancelled,
+            step_names: script_names.to_vec(),
+            steps,
+            awaiting_decision,
+            decision,
+            reader_thread: reader_handle,
             pty_master: pair.master,
             writer,
             status: None,
             log_path: None,
             scroll_offset: 0,
+            pending_run: Some(PendingRun::start(script_names.to_vec(), commands, secrets)),
+            history_id: None,
         }
     }
 
-    /// Create a new RunningCommand with script names (for logging purposes)
-    /// This is now just an alias for new() since script names are not currently used
-    pub fn new_with_names(commands: &[&Command], _script_names: &[String]) -> Self {
-        Self::new(commands)
+    fn is_awaiting_decision(&self) -> bool {
+        self.awaiting_decision.load(Ordering::Acquire)
+    }
+
+    /// Index of the first step that failed, if any
+    fn failed_step(&self) -> Option<usize> {
+        self.steps
+            .lock()
+            .unwrap()
+            .iter()
+            .position(|state| matches!(state, StepState::Failed(_)))
+    }
+
+    /// Index of the step that is running, if any
+    fn current_step(&self) -> Option<usize> {
+        self.steps
+            .lock()
+            .unwrap()
+            .iter()
+            .position(|state| *state == StepState::Running)
+    }
+
+    fn draw_steps(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let steps = self.steps.lock().unwrap();
+        let items = self
+            .step_names
+            .iter()
+            .zip(steps.iter())
+            .map(|(name, state)| {
+                let (icon, style) = match state {
+                    StepState::Pending => (" ", Style::default()),
+                    StepState::Running => ("▶", Style::default().fg(theme.focused_color()).bold()),
+                    StepState::Succeeded => ("✓", Style::default().fg(theme.success_color())),
+                    StepState::Failed(_) => ("✗", Style::default().fg(theme.fail_color()).bold()),
+                    StepState::Skipped => ("-", Style::default().dim()),
+                };
+                Line::styled(format!("{icon} {name}"), style)
+            });
+        let block = Block::bordered()
+            .border_set(border::ROUNDED)
+            .title(" Steps ")
+            .title_alignment(Alignment::Center);
+        frame.render_widget(Clear, area);
+        frame.render_widget(List::new(items).block(block), area);
     }
 
     fn screen(&mut self, size: Size) -> Screen {
@@ -323,6 +650,7 @@ impl RunningCommand {
             && let Ok(exit_status) = handle.join()
         {
             self.status = Some(exit_status.clone());
+            self.record_history(&exit_status);
             return exit_status;
         }
         // Return a default exit status if we can't get the real one
@@ -332,11 +660,34 @@ impl RunningCommand {
             .unwrap_or_else(|| ExitStatus::with_exit_code(1))
     }
 
-    /// Kill the child process
+    /// Save the finished run and its output to the history. Failing to do so is not fatal, the
+    /// run is simply not recorded.
+    fn record_history(&mut self, exit_status: &ExitStatus) {
+        let Some(pending_run) = self.pending_run.take() else {
+            return;
+        };
+        // Give the reader thread a moment to drain the rest of the output
+        let deadline = Instant::now() + Duration::from_millis(200);
+        while !self.reader_thread.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let buffer = self.buffer.lock().unwrap();
+        let signal = exit_status.signal().map(str::to_string);
+        if let Ok(run) = pending_run.finish(exit_status.exit_code(), signal, Some(&buffer)) {
+            self.history_id = Some(run.id);
+        }
+    }
+
+    /// Kill the running step and skip the ones after it
     pub fn kill_child(&mut self) {
-        if !self.is_finished()
-            && let Some(rx) = self.child_killer.take()
-            && let Ok(mut killer) = rx.recv()
+        if self.is_finished() {
+            return;
+        }
+        self.cancelled.store(true, Ordering::Release);
+        if self.is_awaiting_decision() {
+            let _ = self.decision.send(false);
+        }
+        if let Some(killer) = self.child_killer.lock().unwrap().as_mut()
             && let Err(e) = killer.kill()
         {
             eprintln!("Failed to kill child process: {e}");
@@ -411,3 +762,61 @@ impl RunningCommand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::shell_quote;
+    use std::process::Command;
+
+    #[test]
+    fn plain_words_are_left_as_they_are() {
+        assert_eq!(shell_quote("foo-bar_1.2/baz"), "foo-bar_1.2/baz");
+    }
+
+    #[test]
+    fn spaces_are_quoted() {
+        assert_eq!(shell_quote("two words"), "'two words'");
+    }
+
+    #[test]
+    fn single_quotes_are_escaped() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn empty_string_is_quoted() {
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn expansions_and_newlines_are_quoted() {
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote("`id`"), "'`id`'");
+        assert_eq!(shell_quote("a\nb"), "'a\nb'");
+    }
+
+    #[test]
+    fn words_round_trip_through_sh() {
+        for word in [
+            "plain",
+            "two words",
+            "it's",
+            "''",
+            "",
+            "$HOME ${PATH}",
+            "`id` $(id)",
+            "a\nb",
+            "back\\slash",
+            "* ? [a] ~ ; & | < > # !",
+        ] {
+            // `set --` fails the check unless the word stays a single argument
+            let script = format!(
+                r#"set -- {}; [ $# -eq 1 ] && printf %s "$1""#,
+                shell_quote(word)
+            );
+            let output = Command::new("sh").arg("-c").arg(&script).output().unwrap();
+            assert!(output.status.success(), "{script}");
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), word, "{script}");
+        }
+    }
+}
@@ -1,19 +1,29 @@
 use crate::{
     cli::Args,
     confirmation::{ConfirmPrompt, ConfirmStatus},
+    entry_path::entry_path,
+    favorites::Favorites,
     filter::{Filter, SearchAction},
+    flag_filter::FlagFilter,
     float::{Float, FloatContent},
     floating_text::FloatingText,
     hint::{Shortcut, create_shortcut_list},
+    history,
+    history_view::HistoryView,
+    input_form::InputForm,
     logo::Logo,
+    recent::Recent,
     root::check_root_status,
-    running_command::RunningCommand,
+    running_command::{RunningCommand, assemble_script, secret_names},
     shortcuts,
     system_info::SystemInfo,
     theme::Theme,
 };
 #[allow(unused_imports)]
-use osutil_core::{Command, Config, ConfigValues, ListNode, TabList, ego_tree::NodeId};
+use osutil_core::{
+    Command, Config, ConfigValues, EntrySettings, FailurePolicy, ListNode, LookupError, TASK_FLAGS,
+    TabList, ego_tree::NodeId, resolve_entries,
+};
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind},
     layout::Flex,
@@ -27,23 +37,25 @@ const MIN_WIDTH: u16 = 99;
 const MIN_HEIGHT: u16 = 25;
 const FLOAT_SIZE: u16 = 95;
 const CONFIRM_PROMPT_FLOAT_SIZE: u16 = 40;
+const FLAG_FILTER_FLOAT_WIDTH: u16 = 70;
+const FLAG_FILTER_FLOAT_HEIGHT: u16 = 50;
 const LEFT_EXTRA_WIDTH: u16 = 4;
 const TITLE: &str = " OSUTIL ";
 const LIST_HIGHLIGHT_SYMBOL: &str = "> ";
-const ACTIONS_GUIDE: &str = "List of important tasks performed by commands' names:
-
-D  - disk modifications (ex. partitioning) (privileged)
-FI - flatpak installation
-FM - file modification
-I  - installation (privileged)
-K  - kernel modifications (privileged)
-MP - package manager actions
-SI - full system installation
-SS - systemd actions (privileged)
-RP - package removal
-
-P* - privileged *
-";
+/// The Favorites and Recent tabs come before the loaded tabs
+const FAVORITES_TAB: usize = 0;
+const RECENT_TAB: usize = 1;
+/// Number of tabs put before the loaded ones
+const SYNTHETIC_TABS: usize = 2;
+
+fn actions_guide() -> String {
+    let mut guide = String::from("List of important tasks performed by commands' names:\n\n");
+    for (flag, description) in TASK_FLAGS {
+        guide.push_str(&format!("{flag:<2} - {description}\n"));
+    }
+    guide.push_str("\nP* - privileged *\n");
+    guide
+}
 
 pub struct AppState {
     /// Areas of tabs
@@ -65,14 +77,40 @@ pub struct AppState {
     selection: ListState,
     filter: Filter,
     multi_select: bool,
+    /// Show entries hidden by precondition checks, greyed out with the reason
+    show_hidden: bool,
+    /// Task flags whose entries are hidden
+    excluded_flags: Vec<String>,
+    /// Task flags excluded by the config file or the command line. They stay excluded whatever
+    /// is chosen in the task flags window.
+    locked_flags: Vec<String>,
     selected_commands: Vec<Rc<ListNode>>,
+    /// Entries pinned to the Favorites tab
+    favorites: Favorites,
+    /// Entries run before, listed in the Recent tab
+    recent: Recent,
     drawable: bool,
     // tips removed
     size_bypass: bool,
     skip_confirmation: bool,
+    /// Show the assembled script instead of running the selected entries
+    dry_run: bool,
+    /// The confirmation prompt to return to once the script shown from it is closed
+    paused_prompt: Option<Float<ConfirmPrompt>>,
+    /// The auto-executed entries run without confirmation once the windows in front are closed
+    paused_auto_run: bool,
+    /// Where the entries waiting on the input form in front are, counted in the Recent tab once
+    /// the form is submitted
+    pending_recent: Vec<Vec<String>>,
+    /// What to do when an entry of a batch fails, as last chosen in the confirmation prompt
+    on_failure: FailurePolicy,
+    /// Settings from the config file applied to entries when they run
+    entry_settings: EntrySettings,
     mouse_enabled: bool,
     system_info: Option<SystemInfo>,
     logo: Option<Logo>,
+    /// Floating windows waiting to be shown once the current one is closed
+    pending_floats: Vec<Float<dyn FloatContent>>,
 }
 
 pub enum Focus {
@@ -81,12 +119,17 @@ pub enum Focus {
     List,
     FloatingWindow(Float<dyn FloatContent>),
     ConfirmationPrompt(Float<ConfirmPrompt>),
+    FlagFilter(Float<FlagFilter>),
 }
 
 pub struct ListEntry {
     pub node: Rc<ListNode>,
     pub id: NodeId,
     pub has_children: bool,
+    /// Positions of the characters of the name matched by the search
+    pub matched: Vec<usize>,
+    /// The tab and directories of a search result, empty outside of searches
+    pub location: Vec<String>,
 }
 
 struct Areas {
@@ -107,11 +150,55 @@ enum ScrollDir {
 }
 
 impl AppState {
-    pub fn new(args: Args) -> Self {
+    pub fn new(args: Args) -> Result<Self, osutil_core::Error> {
         let root_warning = check_root_status(args.bypass_root);
 
-        let tabs = osutil_core::get_tabs(!args.override_validation);
-        let root_id = tabs[0].tree.root().id();
+        let mut load_errors = Vec::new();
+        let (config, config_errors) = args.read_config();
+        load_errors.extend(
+            config_errors
+                .iter()
+                .map(|e| format!("A configuration file was ignored:\n  {e}")),
+        );
+        let mut tabs = args.load_tabs(&config, true)?;
+        load_errors.extend(tabs.errors().iter().map(|e| match e {
+            osutil_core::Error::DependencyCycle { .. } => {
+                format!("Entry dependencies were not fully applied:\n  {e}")
+            }
+            osutil_core::Error::Invalid { .. } => format!("An entry was skipped:\n  {e}"),
+            _ => format!("A tab was skipped:\n  {e}"),
+        }));
+        // Resolved before the Favorites and Recent tabs are added, which repeat entries of the
+        // other tabs
+        let config_values = config.values(&tabs);
+        load_errors.extend(
+            config_values
+                .unused_entries
+                .iter()
+                .map(|e| format!("A configuration table was ignored:\n  {e}")),
+        );
+
+        let favorites = Favorites::load().unwrap_or_else(|e| {
+            load_errors.push(format!("The favorites could not be read:\n  {e}"));
+            Favorites::default()
+        });
+        let favorites_tab = favorites.tab(&tabs);
+        let mut recent = Recent::load().unwrap_or_else(|e| {
+            load_errors.push(format!(
+                "The recently run entries could not be read:\n  {e}"
+            ));
+            Recent::default()
+        });
+        let recent_tab = recent.tab(&tabs);
+        // Start on the first loaded tab until something is pinned
+        let first_tab = if favorites_tab.tree.root().has_children() {
+            FAVORITES_TAB
+        } else {
+            SYNTHETIC_TABS
+        };
+        tabs.insert(FAVORITES_TAB, favorites_tab);
+        tabs.insert(RECENT_TAB, recent_tab);
+        let root_id = tabs[first_tab].tree.root().id();
 
         let longest_tab_display_len = tabs
             .iter()
@@ -124,48 +211,88 @@ impl AppState {
             theme: args.theme,
             focus: Focus::List,
             tabs,
-            current_tab: ListState::default().with_selected(Some(0)),
+            current_tab: ListState::default().with_selected(Some(first_tab)),
             longest_tab_display_len,
             visit_stack: vec![(root_id, 0usize)],
             selection: ListState::default().with_selected(Some(0)),
             filter: Filter::new(),
             multi_select: false,
+            show_hidden: false,
+            excluded_flags: config.exclude_flags().to_vec(),
+            locked_flags: config.exclude_flags().to_vec(),
             selected_commands: Vec::new(),
+            favorites,
+            recent,
             drawable: false,
             size_bypass: args.size_bypass,
             skip_confirmation: args.skip_confirmation,
+            dry_run: args.dry_run,
+            paused_prompt: None,
+            paused_auto_run: false,
+            pending_recent: Vec::new(),
+            on_failure: FailurePolicy::default(),
+            entry_settings: EntrySettings::default(),
             mouse_enabled: args.mouse,
             system_info: SystemInfo::gather(),
             logo: Logo::load(),
+            pending_floats: Vec::new(),
         };
 
         if let Some(root_warning) = root_warning {
             state.spawn_float(root_warning, FLOAT_SIZE, FLOAT_SIZE);
         }
 
-        state.update_items();
-
-        if let Some(config_path) = args.config {
-            let config = Config::read_config(&config_path, &state.tabs);
-            state.apply_config(config);
+        if !load_errors.is_empty() {
+            let errors = FloatingText::new(load_errors.join("\n\n"), "Load Errors", true);
+            state.queue_float(errors, FLOAT_SIZE, FLOAT_SIZE);
         }
 
-        state
+        state.update_items();
+        state.apply_config(config_values);
+
+        Ok(state)
     }
 
     fn apply_config(&mut self, config_values: ConfigValues) {
         self.skip_confirmation = self.skip_confirmation || config_values.skip_confirmation;
         self.size_bypass = self.size_bypass || config_values.size_bypass;
+        self.on_failure = config_values.on_failure;
+        self.entry_settings = config_values.entries;
 
-        if !config_values.auto_execute_commands.is_empty() {
-            self.selected_commands = config_values.auto_execute_commands;
-            self.handle_initial_auto_execute();
-        }
+        self.selected_commands = config_values.auto_execute_commands;
+        self.handle_initial_auto_execute(&config_values.auto_execute_errors);
     }
 
-    fn handle_initial_auto_execute(&mut self) {
-        if !self.selected_commands.is_empty() {
-            self.spawn_confirmprompt();
+    /// Confirm the entries to auto-execute. Names that did not resolve are shown first, and the
+    /// batch is then always confirmed, since it is not the one the configuration asks for.
+    fn handle_initial_auto_execute(&mut self, errors: &[LookupError]) {
+        if !errors.is_empty() {
+            let problems: Vec<String> = errors.iter().map(|e| format!("  {e}")).collect();
+            let message = format!(
+                "Some entries in auto_execute will not run:\n\n{}",
+                problems.join("\n")
+            );
+            self.queue_float(
+                FloatingText::new(message, "Auto-execute", true),
+                FLOAT_SIZE,
+                FLOAT_SIZE,
+            );
+        }
+        if self.selected_commands.is_empty() {
+            return;
+        }
+        if errors.is_empty() && self.skip_confirmation {
+            if matches!(self.focus, Focus::FloatingWindow(_)) {
+                // Started once the windows in front of it, such as load errors, are read
+                self.paused_auto_run = true;
+            } else {
+                self.handle_confirm_command();
+            }
+        } else if matches!(self.focus, Focus::FloatingWindow(_)) {
+            // Shown once the windows in front of it are closed
+            self.paused_prompt = Some(self.confirm_prompt());
+        } else {
+            self.focus = Focus::ConfirmationPrompt(self.confirm_prompt());
         }
     }
 
@@ -173,30 +300,50 @@ impl AppState {
         if self.skip_confirmation {
             self.handle_confirm_command();
         } else {
-            let cmd_names: Vec<_> = self
-                .selected_commands
-                .iter()
-                .map(|node| node.name.as_str())
-                .collect();
-
-            let prompt = ConfirmPrompt::new(&cmd_names);
-            self.focus = Focus::ConfirmationPrompt(Float::new(
-                Box::new(prompt),
-                CONFIRM_PROMPT_FLOAT_SIZE,
-                CONFIRM_PROMPT_FLOAT_SIZE,
-            ));
+            self.focus = Focus::ConfirmationPrompt(self.confirm_prompt());
         }
     }
 
+    fn confirm_prompt(&self) -> Float<ConfirmPrompt> {
+        // Entries pulled in as prerequisites are listed too, marked as such
+        let (nodes, unmet) = self.tabs.with_prerequisites(&self.selected_commands);
+        let cmd_names: Vec<String> = nodes
+            .iter()
+            .map(|node| {
+                if self.selected_commands.contains(node) {
+                    node.name.clone()
+                } else {
+                    format!("{} (required)", node.name)
+                }
+            })
+            .collect();
+        let cmd_names: Vec<&str> = cmd_names.iter().map(String::as_str).collect();
+
+        let unmet = unmet.iter().map(ToString::to_string).collect();
+        let prompt = ConfirmPrompt::new(&cmd_names, self.on_failure, unmet);
+        Float::new(
+            Box::new(prompt),
+            CONFIRM_PROMPT_FLOAT_SIZE,
+            CONFIRM_PROMPT_FLOAT_SIZE,
+        )
+    }
+
     fn get_list_item_shortcut(&self) -> Box<[Shortcut]> {
         if self.selected_item_is_dir() {
             shortcuts!(("Go to selected dir", ["l", "Right", "Enter"]))
         } else {
-            shortcuts!(
+            let pin = match self.get_selected_node() {
+                Some(node) if self.is_favorite(&node) => "Unpin from favorites",
+                _ => "Pin to favorites",
+            };
+            let mut hints = shortcuts!(
                 ("Run selected command", ["l", "Right", "Enter"]),
                 ("Enable preview", ["p"]),
                 ("Command Description", ["d"])
             )
+            .into_vec();
+            hints.push(Shortcut::new(pin, ["f"]));
+            hints.into_boxed_slice()
         }
     }
 
@@ -231,13 +378,21 @@ impl AppState {
                     ("Previous theme", ["T"]),
                     ("Multi-selection mode", ["v"]),
                 ));
+                hints.push(if self.show_hidden {
+                    Shortcut::new("Hide unsupported entries", ["i"])
+                } else {
+                    Shortcut::new("Show unsupported entries", ["i"])
+                });
                 if self.multi_select {
                     hints.push(Shortcut::new("Select multiple commands", ["Space"]));
                 }
                 hints.extend(shortcuts!(
                     ("Next tab", ["Tab"]),
                     ("Previous tab", ["Shift-Tab"]),
-                    ("Important actions guide", ["g"])
+                    ("Exclude task flags", ["x"]),
+                    ("Important actions guide", ["g"]),
+                    ("Run history", ["H"]),
+                    ("Re-run last batch", ["r"])
                 ));
 
                 ("Command list", hints.into_boxed_slice())
@@ -256,11 +411,16 @@ impl AppState {
                     ("Previous tab", ["Shift-Tab"]),
                     ("Important actions guide", ["g"]),
                     ("Multi-selection mode", ["v"]),
+                    ("Toggle unsupported entries", ["i"]),
+                    ("Exclude task flags", ["x"]),
+                    ("Run history", ["H"]),
+                    ("Re-run last batch", ["r"]),
                 ),
             ),
 
             Focus::FloatingWindow(ref float) => float.get_shortcut_list(),
             Focus::ConfirmationPrompt(ref prompt) => prompt.get_shortcut_list(),
+            Focus::FlagFilter(ref filter) => filter.get_shortcut_list(),
         }
     }
 
@@ -406,12 +566,17 @@ impl AppState {
 
         self.filter.draw_searchbar(frame, chunks[0], &self.theme);
 
-        let title = if self.multi_select {
-            &format!("{TITLE}[Multi-Select] ")
+        let title = match (self.multi_select, self.show_hidden) {
+            (true, true) => &format!("{TITLE}[Multi-Select] [Unsupported Shown] "),
+            (true, false) => &format!("{TITLE}[Multi-Select] "),
+            (false, true) => &format!("{TITLE}[Unsupported Shown] "),
+            (false, false) => TITLE,
+        };
+        let bottom_title = if self.excluded_flags.is_empty() {
+            String::new()
         } else {
-            TITLE
+            format!(" Excluded flags: {} ", self.excluded_flags.join(", "))
         };
-        let bottom_title = "";
 
         let list_focus = matches!(self.focus, Focus::List);
         let list_dim_style = if list_focus {
@@ -445,9 +610,14 @@ impl AppState {
             );
         }
 
+        let in_favorites_tab = self.current_tab.selected() == Some(FAVORITES_TAB);
         items.extend(self.filter.item_list().iter().map(
             |ListEntry {
-                 node, has_children, ..
+                 node,
+                 has_children,
+                 matched,
+                 location,
+                 ..
              }| {
                 let is_selected = self.selected_commands.contains(node);
                 let (indicator, style) = if is_selected {
@@ -460,6 +630,11 @@ impl AppState {
                     };
                     ("", ms_style)
                 };
+                let style = if node.hidden.is_some() {
+                    style.fg(self.theme.hidden_color())
+                } else {
+                    style
+                };
                 if *has_children {
                     Line::styled(
                         format!("{}  {}", self.theme.dir_icon(), node.name,),
@@ -468,18 +643,34 @@ impl AppState {
                     .patch_style(style)
                     .patch_style(list_dim_style)
                 } else {
-                    let left_content =
-                        format!("{}  {} {}", self.theme.cmd_icon(), node.name, indicator);
-                    let right_content = format!("{} ", node.task_list);
+                    let favorite = if in_favorites_tab || !self.is_favorite(node) {
+                        ""
+                    } else {
+                        self.theme.favorite_icon()
+                    };
+                    let mut spans = vec![Span::raw(format!("{}  ", self.theme.cmd_icon()))];
+                    // Search results show where they are, to tell apart entries of the same name
+                    if !location.is_empty() {
+                        spans.push(Span::styled(
+                            format!("{} › ", location.join(" › ")),
+                            self.theme.unfocused_color(),
+                        ));
+                    }
+                    spans.extend(self.highlight_matches(&node.name, matched));
+                    spans.push(Span::raw(format!(" {favorite}{indicator}")));
+                    let right_content = match &node.hidden {
+                        Some(reason) => format!("{reason} "),
+                        None => format!("{} ", node.task_list),
+                    };
+                    let left_width: usize = spans.iter().map(Span::width).sum();
                     let center_space_width =
-                        list_content_width.saturating_sub(left_content.len() + right_content.len());
-                    let center_space = " ".repeat(center_space_width);
-                    Line::styled(
-                        format!("{left_content}{center_space}{right_content}"),
-                        self.theme.cmd_color(),
-                    )
-                    .patch_style(style)
-                    .patch_style(list_dim_style)
+                        list_content_width.saturating_sub(left_width + right_content.len());
+                    spans.push(Span::raw(" ".repeat(center_space_width)));
+                    spans.push(Span::raw(right_content));
+                    Line::from(spans)
+                        .style(self.theme.cmd_color())
+                        .patch_style(style)
+                        .patch_style(list_dim_style)
                 }
             },
         ));
@@ -509,12 +700,48 @@ impl AppState {
         match &mut self.focus {
             Focus::FloatingWindow(float) => float.draw(frame, chunks[1], &self.theme),
             Focus::ConfirmationPrompt(prompt) => prompt.draw(frame, chunks[1], &self.theme),
+            Focus::FlagFilter(filter) => filter.draw(frame, chunks[1], &self.theme),
             _ => {}
         }
 
         frame.render_widget(keybind_para, vertical[1]);
     }
 
+    /// The name split into spans, with the characters matched by the search highlighted
+    fn highlight_matches<'a>(&self, name: &'a str, matched: &[usize]) -> Vec<Span<'a>> {
+        if matched.is_empty() {
+            return vec![Span::raw(name)];
+        }
+        let highlight = Style::new()
+            .fg(self.theme.search_match_color())
+            .underlined();
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let mut in_match = false;
+        for (index, (offset, _)) in name.char_indices().enumerate() {
+            let is_match = matched.contains(&index);
+            if is_match != in_match {
+                if offset > start {
+                    let text = &name[start..offset];
+                    spans.push(if in_match {
+                        Span::styled(text, highlight)
+                    } else {
+                        Span::raw(text)
+                    });
+                }
+                start = offset;
+                in_match = is_match;
+            }
+        }
+        let rest = &name[start..];
+        spans.push(if in_match {
+            Span::styled(rest, highlight)
+        } else {
+            Span::raw(rest)
+        });
+        spans
+    }
+
     fn draw_system_info(&self, frame: &mut Frame, area: Rect) {
         let max_width = area.width as usize;
         let lines = self
@@ -578,6 +805,9 @@ impl AppState {
             Focus::ConfirmationPrompt(confirm) => {
                 confirm.content.handle_mouse_event(event);
             }
+            Focus::FlagFilter(filter) => {
+                filter.content.handle_mouse_event(event);
+            }
             _ => {}
         }
         true
@@ -617,7 +847,25 @@ impl AppState {
         if let Focus::FloatingWindow(command) = &mut self.focus
             && command.handle_key_event(key)
         {
-            self.focus = Focus::List;
+            // An input form has a followup once it is submitted, running its entries
+            let pending_recent = std::mem::take(&mut self.pending_recent);
+            if let Some(followup) = command.content.followup() {
+                self.pending_floats
+                    .insert(0, Float::new(followup, FLOAT_SIZE, FLOAT_SIZE));
+                if !pending_recent.is_empty() {
+                    self.record_recent(pending_recent);
+                }
+            }
+            self.focus = if !self.pending_floats.is_empty() {
+                Focus::FloatingWindow(self.pending_floats.remove(0))
+            } else if let Some(prompt) = self.paused_prompt.take() {
+                Focus::ConfirmationPrompt(prompt)
+            } else {
+                Focus::List
+            };
+            if matches!(self.focus, Focus::List) && std::mem::take(&mut self.paused_auto_run) {
+                self.handle_confirm_command();
+            }
             return true;
         }
 
@@ -640,11 +888,33 @@ impl AppState {
                             }
                         }
                     }
-                    ConfirmStatus::Confirm => self.handle_confirm_command(),
+                    ConfirmStatus::Confirm => {
+                        self.on_failure = confirm.content.on_failure;
+                        self.handle_confirm_command();
+                    }
+                    ConfirmStatus::ShowScript => {
+                        confirm.content.status = ConfirmStatus::None;
+                        let preview = self.script_preview("Script");
+                        if let Focus::ConfirmationPrompt(prompt) =
+                            std::mem::replace(&mut self.focus, Focus::List)
+                        {
+                            self.paused_prompt = Some(prompt);
+                        }
+                        self.spawn_float(preview, FLOAT_SIZE, FLOAT_SIZE);
+                    }
                     ConfirmStatus::None => {}
                 }
             }
 
+            Focus::FlagFilter(filter) => {
+                let close = filter.content.handle_key_event(key);
+                let flags = filter.content.excluded.clone();
+                if close {
+                    self.focus = Focus::List;
+                }
+                self.set_excluded_flags(flags);
+            }
+
             Focus::Search => match self.filter.handle_key(key) {
                 SearchAction::Exit => self.exit_search(),
                 SearchAction::Update => self.update_items(),
@@ -664,6 +934,7 @@ impl AppState {
                 KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
                 KeyCode::Char('p') | KeyCode::Char('P') => self.enable_preview(),
                 KeyCode::Char('d') | KeyCode::Char('D') => self.enable_description(),
+                KeyCode::Char('f') | KeyCode::Char('F') => self.toggle_favorite(),
                 KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => self.handle_enter(),
                 KeyCode::Char('h') | KeyCode::Left => self.go_back(),
                 KeyCode::Char(' ') if self.multi_select => self.toggle_selection(),
@@ -682,6 +953,17 @@ impl AppState {
             KeyCode::Char('/') => self.enter_search(),
             KeyCode::Char('g') | KeyCode::Char('G') => self.enable_task_list_guide(),
             KeyCode::Char('v') | KeyCode::Char('V') => self.toggle_multi_select(),
+            KeyCode::Char('i') | KeyCode::Char('I') => self.toggle_show_hidden(),
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                let filter = FlagFilter::new(&self.excluded_flags, &self.locked_flags);
+                self.focus = Focus::FlagFilter(Float::new(
+                    Box::new(filter),
+                    FLAG_FILTER_FLOAT_WIDTH,
+                    FLAG_FILTER_FLOAT_HEIGHT,
+                ));
+            }
+            KeyCode::Char('H') => self.spawn_float(HistoryView::new(), FLOAT_SIZE, FLOAT_SIZE),
+            KeyCode::Char('r') | KeyCode::Char('R') => self.rerun_last_batch(),
             KeyCode::Char('t') => self.theme.next(),
             KeyCode::Char('T') => self.theme.prev(),
             _ => return false,
@@ -727,9 +1009,89 @@ impl AppState {
         }
     }
 
+    fn toggle_show_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        if !self.show_hidden {
+            self.selected_commands.retain(|node| node.hidden.is_none());
+            // Leave the current directory if it is no longer visible
+            if self.in_hidden_dir() {
+                self.refresh_tab();
+                return;
+            }
+        }
+        self.update_items();
+    }
+
+    /// Whether the current directory, or one of the directories leading to it, is hidden
+    fn in_hidden_dir(&self) -> bool {
+        let tab = &self.tabs[self.current_tab.selected().unwrap()];
+        self.visit_stack
+            .iter()
+            .any(|(id, _)| tab.tree.get(*id).unwrap().value().hidden.is_some())
+    }
+
+    /// Hide the entries with any of `flags` or of the locked flags, and show again those hidden
+    /// for other flags
+    fn set_excluded_flags(&mut self, mut flags: Vec<String>) {
+        for locked in &self.locked_flags {
+            if !flags.iter().any(|flag| flag.eq_ignore_ascii_case(locked)) {
+                flags.push(locked.clone());
+            }
+        }
+        if flags == self.excluded_flags {
+            return;
+        }
+        for tab in &mut self.tabs[SYNTHETIC_TABS..] {
+            tab.exclude_task_flags(&flags);
+        }
+        // Entries that changed were replaced, so the Favorites and Recent tabs are built again
+        self.tabs[FAVORITES_TAB] = self.favorites.tab(&self.tabs[SYNTHETIC_TABS..]);
+        self.tabs[RECENT_TAB] = self.recent.tab(&self.tabs[SYNTHETIC_TABS..]);
+        self.selected_commands
+            .retain(|node| !flags.iter().any(|flag| node.has_task_flag(flag)));
+        self.excluded_flags = flags;
+
+        if !self.show_hidden && self.in_hidden_dir() {
+            self.refresh_tab();
+        } else {
+            self.update_items();
+        }
+    }
+
+    /// Pin the selected entry to the Favorites tab, or unpin it if it already is
+    fn toggle_favorite(&mut self) {
+        let Some(node) = self.get_selected_node() else {
+            return;
+        };
+        let Some(path) = entry_path(&self.tabs[SYNTHETIC_TABS..], &node) else {
+            return;
+        };
+        if let Err(e) = self.favorites.toggle(path) {
+            let message = FloatingText::new(
+                format!("The favorites could not be saved:\n\n{e}"),
+                "Favorites",
+                true,
+            );
+            self.spawn_float(message, FLOAT_SIZE, FLOAT_SIZE);
+        }
+        self.tabs[FAVORITES_TAB] = self.favorites.tab(&self.tabs[SYNTHETIC_TABS..]);
+        if self.current_tab.selected() == Some(FAVORITES_TAB) {
+            self.update_items();
+        }
+    }
+
+    fn is_favorite(&self, node: &Rc<ListNode>) -> bool {
+        self.tabs[FAVORITES_TAB]
+            .tree
+            .root()
+            .children()
+            .any(|favorite| Rc::ptr_eq(favorite.value(), node))
+    }
+
     fn toggle_selection(&mut self) {
         if let Some(node) = self.get_selected_node()
             && node.multi_select
+            && node.hidden.is_none()
         {
             if self.selected_commands.contains(&node) {
                 self.selected_commands.retain(|c| c != &node);
@@ -744,6 +1106,8 @@ impl AppState {
             &self.tabs,
             self.current_tab.selected().unwrap(),
             self.visit_stack.last().unwrap().0,
+            self.show_hidden,
+            SYNTHETIC_TABS,
         );
 
         let len = self.filter.item_list().len();
@@ -797,8 +1161,11 @@ impl AppState {
     }
 
     fn get_selected_description(&self) -> Option<String> {
-        self.get_selected_node()
-            .map(|node| node.description.clone())
+        self.get_selected_node().map(|node| match &node.hidden {
+            Some(reason) if node.description.is_empty() => format!("Unsupported: {reason}"),
+            Some(reason) => format!("{}\n\nUnsupported: {reason}", node.description),
+            None => node.description.clone(),
+        })
     }
 
     pub fn go_to_selected_dir(&mut self) {
@@ -871,7 +1238,7 @@ impl AppState {
 
     fn enable_task_list_guide(&mut self) {
         self.spawn_float(
-            FloatingText::new(ACTIONS_GUIDE.to_string(), "Important Actions Guide", true),
+            FloatingText::new(actions_guide(), "Important Actions Guide", true),
             FLOAT_SIZE,
             FLOAT_SIZE,
         );
@@ -894,6 +1261,17 @@ impl AppState {
             SelectedItem::UpDir => self.enter_parent_directory(),
             SelectedItem::Directory => self.go_to_selected_dir(),
             SelectedItem::Command => {
+                if let Some(node) = self.get_selected_node()
+                    && let Some(reason) = &node.hidden
+                {
+                    let explanation = FloatingText::new(
+                        format!("{} is not supported on this system:\n\n{reason}", node.name),
+                        "Unsupported Entry",
+                        true,
+                    );
+                    self.spawn_float(explanation, FLOAT_SIZE, FLOAT_SIZE);
+                    return;
+                }
                 if self.selected_commands.is_empty()
                     && let Some(node) = self.get_selected_node()
                 {
@@ -905,21 +1283,131 @@ impl AppState {
         }
     }
 
+    /// Select the entries of the most recent run again, by name, and ask to run them
+    fn rerun_last_batch(&mut self) {
+        let run = match history::find_run(None) {
+            Ok(Some(run)) => run,
+            Ok(None) => {
+                let message = FloatingText::new("No runs recorded yet".to_string(), "Re-run", true);
+                self.spawn_float(message, FLOAT_SIZE, FLOAT_SIZE);
+                return;
+            }
+            Err(e) => {
+                let message =
+                    FloatingText::new(format!("Failed to read the history: {e}"), "Re-run", true);
+                self.spawn_float(message, FLOAT_SIZE, FLOAT_SIZE);
+                return;
+            }
+        };
+
+        // The Favorites and Recent tabs repeat entries of the loaded tabs
+        let (nodes, errors) = resolve_entries(&self.tabs[SYNTHETIC_TABS..], &run.entries);
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            let message = FloatingText::new(
+                format!(
+                    "Run #{} cannot be repeated:\n\n{}",
+                    run.id,
+                    errors.join("\n")
+                ),
+                "Re-run",
+                true,
+            );
+            self.spawn_float(message, FLOAT_SIZE, FLOAT_SIZE);
+            return;
+        }
+
+        // A re-run is confirmed even when confirmation is skipped, since a single key starts it
+        self.selected_commands = nodes;
+        self.focus = Focus::ConfirmationPrompt(self.confirm_prompt());
+    }
+
     fn handle_confirm_command(&mut self) {
+        // Skipping the confirmation still shows the prompt when some requirement cannot run
+        let (_, unmet) = self.tabs.with_prerequisites(&self.selected_commands);
+        if !unmet.is_empty() {
+            self.focus = Focus::ConfirmationPrompt(self.confirm_prompt());
+            return;
+        }
+        if self.dry_run {
+            let preview = self.script_preview("Dry run");
+            self.selected_commands.clear();
+            self.spawn_float(preview, FLOAT_SIZE, FLOAT_SIZE);
+            return;
+        }
         let selected_commands = std::mem::take(&mut self.selected_commands);
+        let recent_paths = self.recent_paths(&selected_commands);
+        let (selected_commands, _) = self.tabs.with_prerequisites(&selected_commands);
+        let selected_commands = self.entry_settings.apply(&selected_commands);
+        // The form runs the entries once their inputs are filled in
+        if selected_commands
+            .iter()
+            .any(|node| node.unanswered_inputs().next().is_some())
+        {
+            let form = InputForm::new(selected_commands, self.on_failure);
+            self.spawn_float(form, FLOAT_SIZE, FLOAT_SIZE);
+            self.pending_recent = recent_paths;
+            return;
+        }
+        self.record_recent(recent_paths);
         let commands: Vec<&Command> = selected_commands.iter().map(|node| &node.command).collect();
         let script_names: Vec<String> = selected_commands
             .iter()
             .map(|node| node.name.clone())
             .collect();
-        let running_command = RunningCommand::new_with_names(&commands, &script_names);
+        let running_command = RunningCommand::new_with_names(
+            &commands,
+            &script_names,
+            &secret_names(&selected_commands),
+            self.on_failure,
+        );
         self.spawn_float(running_command, FLOAT_SIZE, FLOAT_SIZE);
     }
 
+    /// Where the entries are, to count their runs in the Recent tab. Prerequisites are not
+    /// counted, since they were not chosen.
+    fn recent_paths(&self, nodes: &[Rc<ListNode>]) -> Vec<Vec<String>> {
+        let loaded_tabs = &self.tabs[SYNTHETIC_TABS..];
+        nodes
+            .iter()
+            .filter_map(|node| entry_path(loaded_tabs, node))
+            .collect()
+    }
+
+    /// Count a run of the entries at `paths` in the Recent tab
+    fn record_recent(&mut self, paths: Vec<Vec<String>>) {
+        // Like the run history, failing to save this does not keep the entries from running
+        let _ = self.recent.record(paths);
+        self.tabs[RECENT_TAB] = self.recent.tab(&self.tabs[SYNTHETIC_TABS..]);
+        if self.current_tab.selected() == Some(RECENT_TAB) {
+            self.update_items();
+        }
+    }
+
+    /// The script the selected entries and their prerequisites would run
+    fn script_preview(&self, title: &str) -> FloatingText<'static> {
+        let (nodes, _) = self.tabs.with_prerequisites(&self.selected_commands);
+        let nodes = self.entry_settings.apply(&nodes);
+        let commands: Vec<&Command> = nodes.iter().map(|node| &node.command).collect();
+        let names: Vec<String> = nodes.iter().map(|node| node.name.clone()).collect();
+        let script = assemble_script(&commands, &names, &secret_names(&nodes));
+        FloatingText::from_script(script, title, false)
+    }
+
     fn spawn_float<T: FloatContent + 'static>(&mut self, float: T, width: u16, height: u16) {
         self.focus = Focus::FloatingWindow(Float::new(Box::new(float), width, height));
     }
 
+    /// Show a floating window after the current one is closed, or right away if there is none
+    fn queue_float<T: FloatContent + 'static>(&mut self, float: T, width: u16, height: u16) {
+        if matches!(self.focus, Focus::FloatingWindow(_)) {
+            self.pending_floats
+                .push(Float::new(Box::new(float), width, height));
+        } else {
+            self.spawn_float(float, width, height);
+        }
+    }
+
     fn enter_search(&mut self) {
         self.focus = Focus::Search;
         self.filter.activate_search();
@@ -35,6 +35,13 @@ impl Theme {
         }
     }
 
+    pub const fn hidden_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::DarkGray,
+            Theme::Compatible => Color::DarkGray,
+        }
+    }
+
     pub const fn tab_color(&self) -> Color {
         match self {
             Theme::Default => Color::White,
@@ -70,6 +77,13 @@ impl Theme {
         }
     }
 
+    pub const fn favorite_icon(&self) -> &'static str {
+        match self {
+            Theme::Default => "★ ",
+            Theme::Compatible => "+ ",
+        }
+    }
+
     pub const fn success_color(&self) -> Color {
         match self {
             Theme::Default => Color::Rgb(5, 255, 55),
@@ -98,6 +112,13 @@ impl Theme {
         }
     }
 
+    pub const fn search_match_color(&self) -> Color {
+        match self {
+            Theme::Default => Color::Yellow,
+            Theme::Compatible => Color::Yellow,
+        }
+    }
+
     pub const fn unfocused_color(&self) -> Color {
         match self {
             Theme::Default => Color::Gray,